@@ -0,0 +1,240 @@
+//! Api key handling
+//!
+//! A client holds one or more api keys. With several keys, a `KeyStrategy` chooses the key
+//! of each request, and requests fail over to the next key when the api rejects a key or
+//! its daily quota is exceeded.
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::form_urlencoded;
+
+const REDACTED: &str = "REDACTED";
+
+/// Accuweather api key, redacted when formatted
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    /// The api key in clear text, to send it to the api
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// `text` with every occurrence of the key replaced by `REDACTED`
+    ///
+    /// The key is also replaced as form-encoded in query parameters, where e.g. `+` and `/`
+    /// become `%2B` and `%2F`.
+    pub(crate) fn redact(&self, text: &str) -> String {
+        if self.0.is_empty() {
+            return text.to_string();
+        }
+        let encoded: String = form_urlencoded::byte_serialize(self.0.as_bytes()).collect();
+        text.replace(&self.0, REDACTED).replace(&encoded, REDACTED)
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        ApiKey(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        ApiKey(key.to_string())
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKey({})", REDACTED)
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// How the api key is sent to the api
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMode {
+    /// As the `apikey` query parameter, accepted by every endpoint
+    #[default]
+    QueryParameter,
+    /// As an `Authorization: Bearer` header, keeping the key out of urls and logs
+    Header,
+}
+
+/// How the key of a request is chosen among the keys of the client
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStrategy {
+    /// Each request uses the next key
+    RoundRobin,
+    /// Each request uses the key that sent the fewest requests
+    LeastUsed,
+    /// Requests use the first key, the next ones only when it fails
+    #[default]
+    PrimaryWithFallback,
+}
+
+/// Health of an api key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyState {
+    /// The key is used following the strategy
+    Active,
+    /// The api answered that the daily quota of the key is exceeded, at `since`.
+    /// The key is used again from the next UTC day.
+    Exhausted { since: SystemTime },
+    /// The api refused the key with this http status (401 or 403).
+    /// The key is only used when no other key is usable, until the states are reset.
+    Rejected(u16),
+}
+
+/// Usage of an api key by the client
+#[derive(Clone, Debug)]
+pub struct KeyUsage {
+    pub key: ApiKey,
+    /// requests sent with the key
+    pub requests: u64,
+    /// requests that failed over to another key
+    pub failures: u64,
+    /// calls left for the key according to the last answer
+    pub rate_limit_remaining: Option<u32>,
+    pub state: KeyState,
+}
+
+impl KeyUsage {
+    fn is_usable(&self, now: SystemTime) -> bool {
+        match self.state {
+            KeyState::Active => true,
+            KeyState::Exhausted { since } => utc_day(since) < utc_day(now),
+            KeyState::Rejected(_) => false,
+        }
+    }
+}
+
+fn utc_day(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86400)
+        .unwrap_or(0)
+}
+
+/// The api keys of a client with their usage
+#[derive(Debug)]
+pub(crate) struct KeyPool {
+    strategy: KeyStrategy,
+    usage: Mutex<Vec<KeyUsage>>,
+    next: AtomicUsize,
+}
+
+impl KeyPool {
+    pub(crate) fn new(keys: Vec<ApiKey>, strategy: KeyStrategy) -> Self {
+        let usage = keys
+            .into_iter()
+            .map(|key| KeyUsage {
+                key,
+                requests: 0,
+                failures: 0,
+                rate_limit_remaining: None,
+                state: KeyState::Active,
+            })
+            .collect();
+        KeyPool {
+            strategy,
+            usage: Mutex::new(usage),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Choose the key of the next request among the keys not `tried` yet, preferring
+    /// usable ones, and count the request.
+    pub(crate) fn select(&self, tried: &[usize]) -> Option<(usize, ApiKey)> {
+        let mut usage = self.usage.lock().unwrap();
+        let now = SystemTime::now();
+        let untried: Vec<usize> = (0..usage.len()).filter(|i| !tried.contains(i)).collect();
+        let usable: Vec<usize> = untried
+            .iter()
+            .cloned()
+            .filter(|&i| usage[i].is_usable(now))
+            .collect();
+        let candidates = if usable.is_empty() { untried } else { usable };
+        let index = match self.strategy {
+            KeyStrategy::PrimaryWithFallback => candidates.first().cloned(),
+            KeyStrategy::LeastUsed => candidates
+                .iter()
+                .cloned()
+                .min_by_key(|&i| usage[i].requests),
+            KeyStrategy::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::Relaxed) % usage.len();
+                candidates
+                    .iter()
+                    .cloned()
+                    .min_by_key(|&i| (i + usage.len() - start) % usage.len())
+            }
+        }?;
+        let key = &mut usage[index];
+        key.requests += 1;
+        if let KeyState::Exhausted { .. } = key.state {
+            if key.is_usable(now) {
+                key.state = KeyState::Active;
+            }
+        }
+        Some((index, key.key.clone()))
+    }
+
+    pub(crate) fn record_success(&self, index: usize, rate_limit_remaining: Option<u32>) {
+        let mut usage = self.usage.lock().unwrap();
+        let key = &mut usage[index];
+        key.state = KeyState::Active;
+        if rate_limit_remaining.is_some() {
+            key.rate_limit_remaining = rate_limit_remaining;
+        }
+    }
+
+    pub(crate) fn record_failure(&self, index: usize, state: KeyState) {
+        let mut usage = self.usage.lock().unwrap();
+        let key = &mut usage[index];
+        key.failures += 1;
+        key.state = state;
+        if let KeyState::Exhausted { .. } = state {
+            key.rate_limit_remaining = Some(0);
+        }
+    }
+
+    /// Whether every key is exhausted, or had no calls left at its last answer
+    pub(crate) fn quota_exhausted(&self) -> bool {
+        let now = SystemTime::now();
+        self.usage
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|key| match key.state {
+                KeyState::Exhausted { .. } => !key.is_usable(now),
+                _ => key.rate_limit_remaining == Some(0),
+            })
+    }
+
+    pub(crate) fn usage(&self) -> Vec<KeyUsage> {
+        self.usage.lock().unwrap().clone()
+    }
+
+    pub(crate) fn reset_states(&self) {
+        for key in self.usage.lock().unwrap().iter_mut() {
+            key.state = KeyState::Active;
+        }
+    }
+
+    /// `text` with every key of the pool redacted
+    pub(crate) fn redact(&self, text: &str) -> String {
+        self.usage
+            .lock()
+            .unwrap()
+            .iter()
+            .fold(text.to_string(), |text, usage| usage.key.redact(&text))
+    }
+}
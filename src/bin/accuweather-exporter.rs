@@ -0,0 +1,419 @@
+//! Prometheus exporter of the current conditions of a set of locations
+//!
+//! Polls the current conditions of each location in turn, spacing the calls so that they
+//! fit in the daily quota of the api key, and serves the last conditions as gauges on
+//! `/metrics`. The api key is read from `ACCUWEATHER_*` environment variables, or from a
+//! TOML file given with `--config`. See the `config` module of the library.
+extern crate accuweather;
+
+use accuweather::config::Config;
+use accuweather::types::CurrentCondition;
+use accuweather::Accuweather;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{env, process, thread};
+
+const USAGE: &str = "Usage: accuweather-exporter [OPTIONS] --locations <KEY,...>
+
+Options:
+      --locations <KEY,...>     location keys to poll
+      --listen <ADDRESS>        address of the metrics endpoint, defaults to 0.0.0.0:9117
+      --daily-quota <CALLS>     calls allowed per day to the api key, defaults to 50
+      --min-interval <SECONDS>  minimal time between two calls, defaults to 300
+  -c, --config <FILE>           read settings from a TOML file instead of the environment
+  -h, --help                    print this help";
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Time a metrics request may take to be read or written
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connections served at once, the others waiting to be accepted
+const WORKERS: usize = 4;
+
+struct Options {
+    locations: Vec<i32>,
+    listen: String,
+    daily_quota: u64,
+    min_interval: u64,
+    config: Option<String>,
+}
+
+/// A metric: name, help and how to get its value
+type Metric<T, V> = (&'static str, &'static str, fn(&T) -> V);
+
+/// Gauges exported for current conditions, in metric units
+const GAUGES: &[Metric<CurrentCondition, f32>] = &[
+    ("temperature_celsius", "Temperature.", |c| {
+        c.temperature.metric.value
+    }),
+    (
+        "real_feel_temperature_celsius",
+        "RealFeel temperature.",
+        |c| c.real_feel_temperature.metric.value,
+    ),
+    ("relative_humidity_percent", "Relative humidity.", |c| {
+        c.relative_humidity as f32
+    }),
+    ("dew_point_celsius", "Dew point.", |c| {
+        c.dew_point.metric.value
+    }),
+    ("pressure_hectopascals", "Atmospheric pressure.", |c| {
+        c.pressure.metric.value
+    }),
+    ("wind_speed_kilometers_per_hour", "Wind speed.", |c| {
+        c.wind.speed.metric.value
+    }),
+    ("wind_gust_kilometers_per_hour", "Wind gust speed.", |c| {
+        c.wind_gust.speed.metric.value
+    }),
+    (
+        "wind_direction_degrees",
+        "Direction the wind blows from.",
+        |c| c.wind.direction.degrees,
+    ),
+    ("uv_index", "UV index.", |c| c.uv_index as f32),
+    ("visibility_kilometers", "Visibility.", |c| {
+        c.visibility.metric.value
+    }),
+    ("cloud_cover_percent", "Cloud cover.", |c| {
+        c.cloud_cover as f32
+    }),
+    (
+        "precipitation_1h_millimeters",
+        "Precipitation of the last hour.",
+        |c| c.precip1hr.metric.value,
+    ),
+];
+
+/// State of a polled location
+#[derive(Default)]
+struct LocationMetrics {
+    conditions: Option<CurrentCondition>,
+    calls: u64,
+    errors: u64,
+}
+
+/// Metrics shared by the poller and the http endpoint
+#[derive(Default)]
+struct Metrics {
+    locations: BTreeMap<i32, LocationMetrics>,
+    quota_remaining: Option<u32>,
+}
+
+fn main() {
+    let options = match parse_options(env::args().skip(1).collect()) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    let config = match options.config {
+        Some(ref path) => Config::from_file(path),
+        None => Config::from_env(),
+    };
+    let client = match config.and_then(Config::build) {
+        Ok(client) => client,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    };
+    let listener = match TcpListener::bind(&options.listen) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("can't listen on {}: {}", options.listen, error);
+            process::exit(1);
+        }
+    };
+    let metrics = Arc::new(Mutex::new(Metrics::default()));
+    let interval = poll_interval(&options);
+    eprintln!(
+        "polling {} locations, one call every {}s, metrics on http://{}/metrics",
+        options.locations.len(),
+        interval.as_secs(),
+        options.listen
+    );
+    let poller_metrics = Arc::clone(&metrics);
+    let locations = options.locations.clone();
+    thread::spawn(move || poll(client, &locations, interval, &poller_metrics));
+    listen(listener, metrics);
+}
+
+/// Serve the http requests of `listener` on `WORKERS` threads
+fn listen(listener: TcpListener, metrics: Arc<Mutex<Metrics>>) {
+    for _ in 1..WORKERS {
+        match listener.try_clone() {
+            Ok(listener) => {
+                let metrics = Arc::clone(&metrics);
+                thread::spawn(move || accept(&listener, &metrics));
+            }
+            Err(error) => eprintln!("can't share the metrics listener: {}", error),
+        }
+    }
+    accept(&listener, &metrics);
+}
+
+/// Serve the connections of `listener` one at a time
+fn accept(listener: &TcpListener, metrics: &Mutex<Metrics>) {
+    for stream in listener.incoming().flatten() {
+        if let Err(error) = serve(stream, metrics) {
+            eprintln!("metrics request failed: {}", error);
+        }
+    }
+}
+
+fn parse_options(args: Vec<String>) -> Result<Options, String> {
+    let mut options = Options {
+        locations: Vec::new(),
+        listen: "0.0.0.0:9117".to_string(),
+        daily_quota: 50,
+        min_interval: 300,
+        config: None,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value of {}", name))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--locations" => {
+                options.locations = value("--locations")?
+                    .split(',')
+                    .map(|key| {
+                        key.trim()
+                            .parse()
+                            .map_err(|_| format!("invalid location key {}", key))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "--listen" => options.listen = value("--listen")?,
+            "--daily-quota" => {
+                options.daily_quota = value("--daily-quota")?
+                    .parse()
+                    .map_err(|_| "invalid --daily-quota".to_string())?;
+            }
+            "--min-interval" => {
+                options.min_interval = value("--min-interval")?
+                    .parse()
+                    .map_err(|_| "invalid --min-interval".to_string())?;
+            }
+            "-c" | "--config" => options.config = Some(value("--config")?),
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+    if options.locations.is_empty() {
+        return Err("missing --locations".to_string());
+    }
+    if options.daily_quota == 0 {
+        return Err("invalid --daily-quota".to_string());
+    }
+    Ok(options)
+}
+
+/// Time between two calls, spreading the daily quota over the day
+fn poll_interval(options: &Options) -> Duration {
+    Duration::from_secs((DAY / options.daily_quota).max(options.min_interval))
+}
+
+/// Poll the locations in turn, forever
+fn poll(mut client: Accuweather, locations: &[i32], interval: Duration, metrics: &Mutex<Metrics>) {
+    for &location in locations.iter().cycle() {
+        client.set_location(Some(location));
+        let result = client.get_current_conditions_with_meta();
+        {
+            let mut metrics = metrics.lock().unwrap();
+            let quota_remaining = result
+                .as_ref()
+                .ok()
+                .and_then(|response| response.rate_limit_remaining);
+            if quota_remaining.is_some() {
+                metrics.quota_remaining = quota_remaining;
+            }
+            let entry = metrics.locations.entry(location).or_default();
+            entry.calls += 1;
+            match result {
+                Ok(response) => entry.conditions = response.body.into_iter().next(),
+                Err(error) => {
+                    entry.errors += 1;
+                    eprintln!("location {}: {}", location, error);
+                }
+            }
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Answer an http request, with the metrics on `/metrics`
+fn serve(mut stream: TcpStream, metrics: &Mutex<Metrics>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or("");
+    let (status, content_type, body) = if path == "/metrics" {
+        (
+            "200 OK",
+            "text/plain; version=0.0.4",
+            render(&metrics.lock().unwrap()),
+        )
+    } else if path == "/" {
+        (
+            "200 OK",
+            "text/html",
+            "<a href=\"/metrics\">Metrics</a>\n".to_string(),
+        )
+    } else {
+        ("404 Not Found", "text/plain", "not found\n".to_string())
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+/// Render the metrics in the Prometheus text format
+fn render(metrics: &Metrics) -> String {
+    let mut output = String::new();
+    for (name, help, value) in GAUGES {
+        let _ = writeln!(output, "# HELP accuweather_{} {}", name, help);
+        let _ = writeln!(output, "# TYPE accuweather_{} gauge", name);
+        for (location, state) in &metrics.locations {
+            if let Some(conditions) = &state.conditions {
+                let _ = writeln!(
+                    output,
+                    "accuweather_{}{{location=\"{}\"}} {}",
+                    name,
+                    location,
+                    value(conditions)
+                );
+            }
+        }
+    }
+    let counters: [Metric<LocationMetrics, u64>; 2] = [
+        (
+            "api_calls_total",
+            "Calls to the current conditions api.",
+            |s| s.calls,
+        ),
+        (
+            "api_errors_total",
+            "Failed calls to the current conditions api.",
+            |s| s.errors,
+        ),
+    ];
+    for (name, help, value) in &counters {
+        let _ = writeln!(output, "# HELP accuweather_{} {}", name, help);
+        let _ = writeln!(output, "# TYPE accuweather_{} counter", name);
+        for (location, state) in &metrics.locations {
+            let _ = writeln!(
+                output,
+                "accuweather_{}{{location=\"{}\"}} {}",
+                name,
+                location,
+                value(state)
+            );
+        }
+    }
+    if let Some(remaining) = metrics.quota_remaining {
+        let _ = writeln!(
+            output,
+            "# HELP accuweather_quota_remaining Calls left for the api key, from the last answer."
+        );
+        let _ = writeln!(output, "# TYPE accuweather_quota_remaining gauge");
+        let _ = writeln!(output, "accuweather_quota_remaining {}", remaining);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Read;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = parse_options(args(&[
+            "--locations",
+            "623, 335951",
+            "--daily-quota",
+            "100",
+        ]))
+        .unwrap();
+        assert_eq!(options.locations, vec![623, 335951]);
+        assert_eq!(poll_interval(&options), Duration::from_secs(864));
+        assert!(parse_options(args(&["--locations", "paris"])).is_err());
+        assert!(parse_options(args(&["--daily-quota", "100"])).is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let conditions: Vec<CurrentCondition> =
+            serde_json::from_str(&fs::read_to_string("assets/conditions_details.json").unwrap())
+                .unwrap();
+        let mut metrics = Metrics {
+            quota_remaining: Some(42),
+            ..Default::default()
+        };
+        metrics.locations.insert(
+            623,
+            LocationMetrics {
+                conditions: conditions.into_iter().next(),
+                calls: 3,
+                errors: 1,
+            },
+        );
+        metrics.locations.insert(335951, LocationMetrics::default());
+        let output = render(&metrics);
+        assert!(output.contains("# TYPE accuweather_temperature_celsius gauge\n"));
+        assert!(output.contains("accuweather_temperature_celsius{location=\"623\"} 27.9\n"));
+        assert!(!output.contains("accuweather_temperature_celsius{location=\"335951\"}"));
+        assert!(output.contains("accuweather_api_calls_total{location=\"623\"} 3\n"));
+        assert!(output.contains("accuweather_api_errors_total{location=\"335951\"} 0\n"));
+        assert!(output.contains("accuweather_quota_remaining 42\n"));
+    }
+
+    #[test]
+    fn test_idle_connection_does_not_block_scrapes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let metrics = Arc::new(Mutex::new(Metrics {
+            quota_remaining: Some(7),
+            ..Default::default()
+        }));
+        thread::spawn(move || listen(listener, metrics));
+        let _idle: Vec<TcpStream> = (1..WORKERS)
+            .map(|_| TcpStream::connect(address).unwrap())
+            .collect();
+        let mut scrape = TcpStream::connect(address).unwrap();
+        scrape
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        scrape
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut answer = String::new();
+        scrape.read_to_string(&mut answer).unwrap();
+        assert!(answer.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(answer.contains("accuweather_quota_remaining 7\n"));
+    }
+}
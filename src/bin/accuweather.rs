@@ -0,0 +1,763 @@
+//! Command-line client for the Accuweather api
+//!
+//! The api key and defaults are read from `ACCUWEATHER_*` environment variables, or from
+//! a TOML file given with `--config`. See the `config` module of the library.
+extern crate accuweather;
+
+use accuweather::auth::KeyState;
+use accuweather::config::{Config, ConfigError, Units};
+use accuweather::periods::{
+    AirQualityDailyPeriod, AirQualityHourlyPeriod, DailyPeriod, HourlyPeriod,
+};
+use accuweather::types::*;
+use accuweather::{Accuweather, AccuweatherHttpError, AccuweatherInvalidParameterError};
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::io::{self, Write};
+use std::{env, fmt, process};
+
+const USAGE: &str = "Usage: accuweather [OPTIONS] <COMMAND> [ARGS]
+
+Commands:
+  search <TEXT>                      search cities by name
+  conditions                         current conditions
+  hourly [HOURS]                     hourly forecasts: 1, 12 (default), 24, 72, 120 or 240 hours
+  daily [DAYS]                       daily forecasts: 1, 5 (default), 10, 15, 25 or 45 days
+  quarters [DAYS]                    quarter-day forecasts: 1, 5 (default), 10 or 15 days
+  air-quality                        current air quality
+  air-quality hourly [HOURS]         air quality forecasts: 1, 12 (default), 24, 48, 72 or 96 hours
+  air-quality daily [DAYS]           air quality forecasts: 1 or 4 (default) days
+  climo <actuals|normals|records> <YYYY-MM-DD>
+                                     climatology of a day
+  climo summary <YYYY-MM>            climatology of every day of a month
+  imagery [RESOLUTION]               radar and satellite frames: 480x480 (default),
+                                     640x480 or 1024x1024
+
+Options:
+  -l, --location <KEY>               location key, see the search command
+  -L, --language <LANGUAGE>          language of the answers, e.g. fr-fr
+  -f, --format <table|json|csv>      output format, defaults to table
+  -c, --config <FILE>                read settings from a TOML file instead of the environment
+      --imperial                     imperial units instead of metric
+  -h, --help                         print this help
+
+Exit codes:
+  0 success, 1 other errors, 2 bad input, 3 api key refused, 4 quota exceeded";
+
+const COMMANDS: &[&str] = &[
+    "search",
+    "conditions",
+    "hourly",
+    "daily",
+    "quarters",
+    "air-quality",
+    "climo",
+    "imagery",
+];
+
+const EXIT_ERROR: i32 = 1;
+const EXIT_BAD_INPUT: i32 = 2;
+const EXIT_AUTH: i32 = 3;
+const EXIT_QUOTA: i32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug)]
+struct Options {
+    location: Option<i32>,
+    language: Option<String>,
+    format: Format,
+    config: Option<String>,
+    imperial: bool,
+    command: Vec<String>,
+}
+
+/// Error of the command line, with its exit code
+#[derive(Debug)]
+struct CliError {
+    code: i32,
+    message: String,
+}
+
+impl CliError {
+    fn usage<T: fmt::Display>(message: T) -> Self {
+        CliError {
+            code: EXIT_BAD_INPUT,
+            message: format!("{}\n\n{}", message, USAGE),
+        }
+    }
+}
+
+impl From<ConfigError> for CliError {
+    fn from(error: ConfigError) -> Self {
+        CliError {
+            code: EXIT_BAD_INPUT,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Rows of an answer, printed as a table or csv
+struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(error) = run(args) {
+        eprintln!("{}", error.message);
+        process::exit(error.code);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), CliError> {
+    let options = parse_options(args)?;
+    let mut client = match options.config {
+        Some(ref path) => Config::from_file(path)?,
+        None => Config::from_env()?,
+    }
+    .build()?;
+    if options.location.is_some() {
+        client.set_location(options.location);
+    }
+    if let Some(language) = options.language.clone() {
+        client.language = language;
+    }
+    if options.imperial {
+        client.set_units(Units::Imperial);
+    }
+    let (value, table) =
+        run_command(&client, &options.command).map_err(|error| classify(&client, error))?;
+    let output = match options.format {
+        Format::Json => serde_json::to_string_pretty(&value).unwrap() + "\n",
+        Format::Csv => format_csv(&table),
+        Format::Table => format_table(&table),
+    };
+    // a closed stdout, e.g. piped to head, isn't an error
+    let _ = io::stdout().write_all(output.as_bytes());
+    Ok(())
+}
+
+fn parse_options(args: Vec<String>) -> Result<Options, CliError> {
+    let mut options = Options {
+        location: None,
+        language: None,
+        format: Format::Table,
+        config: None,
+        imperial: false,
+        command: Vec::new(),
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| CliError::usage(format!("missing value of {}", name)))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "-l" | "--location" => {
+                let location = value("--location")?;
+                options.location =
+                    Some(location.parse().map_err(|_| {
+                        CliError::usage(format!("invalid location key {}", location))
+                    })?);
+            }
+            "-L" | "--language" => options.language = Some(value("--language")?),
+            "-f" | "--format" => {
+                options.format = match value("--format")?.as_str() {
+                    "table" => Format::Table,
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    format => return Err(CliError::usage(format!("invalid format {}", format))),
+                }
+            }
+            "-c" | "--config" => options.config = Some(value("--config")?),
+            "--imperial" => options.imperial = true,
+            _ if arg.starts_with('-') && options.command.is_empty() => {
+                return Err(CliError::usage(format!("unknown option {}", arg)))
+            }
+            _ => options.command.push(arg),
+        }
+    }
+    match options.command.first() {
+        None => Err(CliError::usage("missing command")),
+        Some(command) if !COMMANDS.contains(&command.as_str()) => {
+            Err(CliError::usage(format!("unknown command {}", command)))
+        }
+        Some(_) => Ok(options),
+    }
+}
+
+fn classify(client: &Accuweather, error: Box<dyn Error>) -> CliError {
+    if error.is::<AccuweatherInvalidParameterError>() {
+        return CliError {
+            code: EXIT_BAD_INPUT,
+            message: format!("{}, see accuweather --help", error),
+        };
+    }
+    let code = if error.is::<ConfigError>() {
+        EXIT_BAD_INPUT
+    } else if let Some(http) = error.downcast_ref::<AccuweatherHttpError>() {
+        let exhausted = client
+            .key_usage()
+            .iter()
+            .any(|usage| matches!(usage.state, KeyState::Exhausted { .. }));
+        match http.status {
+            Some(400) | Some(404) => EXIT_BAD_INPUT,
+            Some(401) | Some(403) => EXIT_AUTH,
+            Some(429) => EXIT_QUOTA,
+            Some(503) if exhausted => EXIT_QUOTA,
+            _ => EXIT_ERROR,
+        }
+    } else {
+        EXIT_ERROR
+    };
+    CliError {
+        code,
+        message: error.to_string(),
+    }
+}
+
+fn number_argument(argument: Option<&String>, default: u16) -> Result<u16, Box<dyn Error>> {
+    match argument {
+        Some(number) => Ok(number
+            .parse()
+            .map_err(|_| AccuweatherInvalidParameterError::new("period", number, "a number"))?),
+        None => Ok(default),
+    }
+}
+
+fn run_command(client: &Accuweather, command: &[String]) -> Result<(Value, Table), Box<dyn Error>> {
+    if client.location.is_none() && command[0] != "search" {
+        return Err(Box::new(ConfigError::Missing(
+            "location, set ACCUWEATHER_LOCATION or --location".to_string(),
+        )));
+    }
+    let metric = client.units == Units::Metric;
+    match command[0].as_str() {
+        "search" => {
+            let text = command
+                .get(1)
+                .ok_or_else(|| AccuweatherInvalidParameterError::new("search", "", "a text"))?;
+            let (value, locations) = client.search_locations_raw(text)?;
+            Ok((value, search_table(&locations)))
+        }
+        "conditions" => {
+            let (value, conditions) = client.get_current_conditions_raw()?;
+            Ok((value, conditions_table(&conditions, metric)))
+        }
+        "hourly" => {
+            let period = HourlyPeriod::try_from(number_argument(command.get(1), 12)?)?;
+            let (value, forecasts) = client.get_hourly_forecasts_raw(period)?;
+            Ok((value, hourly_table(&forecasts)))
+        }
+        "daily" => {
+            let period = DailyPeriod::try_from(number_argument(command.get(1), 5)?)?;
+            let (value, answer) = client.get_daily_forecasts_raw(period)?;
+            Ok((value, daily_table(&answer.daily_forecasts)))
+        }
+        "quarters" => {
+            let period = DailyPeriod::try_from(number_argument(command.get(1), 5)?)?;
+            let (value, forecasts) = client.get_quarter_forecasts_raw(period)?;
+            Ok((value, quarters_table(&forecasts)))
+        }
+        "air-quality" => match command.get(1).map(String::as_str) {
+            None => {
+                let (value, air_quality) = client.get_air_quality_current_conditions_raw()?;
+                Ok((value, air_quality_table(&air_quality)))
+            }
+            Some("hourly") => {
+                let hours = number_argument(command.get(2), 12)?;
+                let period = AirQualityHourlyPeriod::try_from(hours)?;
+                let (value, forecasts) = client.get_air_quality_hourly_forecasts_raw(period)?;
+                Ok((value, air_quality_forecasts_table(&forecasts)))
+            }
+            Some("daily") => {
+                let period = AirQualityDailyPeriod::try_from(number_argument(command.get(2), 4)?)?;
+                let (value, forecasts) = client.get_air_quality_daily_forecasts_raw(period)?;
+                Ok((value, air_quality_forecasts_table(&forecasts)))
+            }
+            Some(kind) => Err(Box::new(AccuweatherInvalidParameterError::new(
+                "air-quality forecast",
+                kind,
+                "hourly or daily",
+            ))),
+        },
+        "climo" => {
+            let kind = command.get(1).map_or("", String::as_str);
+            if kind == "summary" {
+                let (year, month) = parse_month(command.get(2))?;
+                let (value, answer) = client.get_climo_month_summary_raw(year, month)?;
+                return Ok((value, climo_summary_table(&answer)));
+            }
+            let (year, month, day) = match kind {
+                "actuals" | "normals" | "records" => parse_date(command.get(2))?,
+                _ => {
+                    return Err(Box::new(AccuweatherInvalidParameterError::new(
+                        "climo",
+                        kind,
+                        "actuals, normals, records or summary",
+                    )))
+                }
+            };
+            match kind {
+                "actuals" => {
+                    let (value, answer) = client.get_climo_actuals_raw(year, month, day)?;
+                    Ok((value, climo_table(&answer.date, &answer.actuals)))
+                }
+                "normals" => {
+                    let (value, answer) = client.get_climo_normals_raw(year, month, day)?;
+                    Ok((value, climo_table(&answer.date, &answer.normals)))
+                }
+                _ => {
+                    let (value, answer) = client.get_climo_records_raw(year, month, day)?;
+                    Ok((value, climo_records_table(&answer)))
+                }
+            }
+        }
+        "imagery" => {
+            let resolution = command.get(1).map_or("480x480", String::as_str);
+            let (value, answer) = client.get_imagery_raw(resolution)?;
+            Ok((value, imagery_table(&answer.frames())))
+        }
+        command => Err(Box::new(AccuweatherInvalidParameterError::new(
+            "command",
+            command,
+            COMMANDS.join(", "),
+        ))),
+    }
+}
+
+fn parse_date(date: Option<&String>) -> Result<(i32, u8, u8), AccuweatherInvalidParameterError> {
+    let date = date.map_or("", String::as_str);
+    let error = || AccuweatherInvalidParameterError::new("date", date, "YYYY-MM-DD");
+    let parts: Vec<&str> = date.split('-').collect();
+    match parts.as_slice() {
+        [year, month, day] => Ok((
+            year.parse().map_err(|_| error())?,
+            month.parse().map_err(|_| error())?,
+            day.parse().map_err(|_| error())?,
+        )),
+        _ => Err(error()),
+    }
+}
+
+fn parse_month(month: Option<&String>) -> Result<(i32, u8), AccuweatherInvalidParameterError> {
+    let month = month.map_or("", String::as_str);
+    let error = || AccuweatherInvalidParameterError::new("month", month, "YYYY-MM");
+    let parts: Vec<&str> = month.split('-').collect();
+    match parts.as_slice() {
+        [year, month] => Ok((
+            year.parse().map_err(|_| error())?,
+            month.parse().map_err(|_| error())?,
+        )),
+        _ => Err(error()),
+    }
+}
+
+fn measurement(measurement: &ConditionMeasurement, metric: bool) -> String {
+    if metric {
+        measurement.metric.to_string()
+    } else {
+        measurement.imperial.to_string()
+    }
+}
+
+fn search_table(locations: &[Location]) -> Table {
+    Table {
+        headers: vec!["Key", "Name", "Area", "Country", "Latitude", "Longitude"],
+        rows: locations
+            .iter()
+            .map(|location| {
+                vec![
+                    location.key.clone(),
+                    location.localized_name.clone(),
+                    location.administrative_area.localized_name.clone(),
+                    location.country.localized_name.clone(),
+                    location.geo_position.latitude.to_string(),
+                    location.geo_position.longitude.to_string(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn conditions_table(conditions: &[CurrentCondition], metric: bool) -> Table {
+    Table {
+        headers: vec![
+            "Time",
+            "Weather",
+            "Temperature",
+            "RealFeel",
+            "Humidity",
+            "Wind",
+            "Pressure",
+        ],
+        rows: conditions
+            .iter()
+            .map(|condition| {
+                vec![
+                    condition.local_observation_date_time.clone(),
+                    format!(
+                        "{} {}",
+                        condition.weather_icon.emoji(),
+                        condition.weather_text
+                    ),
+                    measurement(&condition.temperature, metric),
+                    measurement(&condition.real_feel_temperature, metric),
+                    format!("{}%", condition.relative_humidity),
+                    format!(
+                        "{} {}",
+                        measurement(&condition.wind.speed, metric),
+                        condition.wind.direction.localized
+                    ),
+                    measurement(&condition.pressure, metric),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn hourly_table(forecasts: &[HourlyForecast]) -> Table {
+    Table {
+        headers: vec![
+            "Time",
+            "Weather",
+            "Temperature",
+            "RealFeel",
+            "Precipitation",
+            "Wind",
+        ],
+        rows: forecasts
+            .iter()
+            .map(|forecast| {
+                vec![
+                    forecast.date_time.clone(),
+                    format!("{} {}", forecast.weather_icon.emoji(), forecast.icon_phrase),
+                    forecast.temperature.to_string(),
+                    forecast.real_feel_temperature.to_string(),
+                    format!(
+                        "{}% {}",
+                        forecast.precipitation_probability, forecast.total_liquid
+                    ),
+                    format!(
+                        "{} {}",
+                        forecast.wind.speed, forecast.wind.direction.localized
+                    ),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn daily_table(forecasts: &[DailyForecast]) -> Table {
+    Table {
+        headers: vec![
+            "Date",
+            "Day",
+            "Night",
+            "Minimum",
+            "Maximum",
+            "Precipitation",
+        ],
+        rows: forecasts
+            .iter()
+            .map(|forecast| {
+                vec![
+                    forecast.date.clone(),
+                    format!("{} {}", forecast.day.icon.emoji(), forecast.day.icon_phrase),
+                    format!(
+                        "{} {}",
+                        forecast.night.icon.emoji(),
+                        forecast.night.icon_phrase
+                    ),
+                    forecast.temperature.minimum.to_string(),
+                    forecast.temperature.maximum.to_string(),
+                    format!(
+                        "{}% / {}%",
+                        forecast.day.precipitation_probability,
+                        forecast.night.precipitation_probability
+                    ),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn quarters_table(forecasts: &[QuarterForecast]) -> Table {
+    Table {
+        headers: vec![
+            "Date",
+            "Quarter",
+            "Weather",
+            "Minimum",
+            "Maximum",
+            "Precipitation",
+        ],
+        rows: forecasts
+            .iter()
+            .map(|forecast| {
+                vec![
+                    forecast.effective_date.clone(),
+                    forecast.name().unwrap_or_default().to_string(),
+                    format!("{} {}", forecast.icon.emoji(), forecast.icon_phrase),
+                    forecast.temperature.minimum.to_string(),
+                    forecast.temperature.maximum.to_string(),
+                    format!("{}%", forecast.precipitation_probability),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn air_quality_table(air_quality: &AirQuality) -> Table {
+    let mut rows = vec![vec![
+        "Overall".to_string(),
+        air_quality.overall_index.to_string(),
+        air_quality.category.clone(),
+        String::new(),
+    ]];
+    rows.extend(air_quality.pollutants.iter().map(|pollutant| {
+        vec![
+            pollutant.name.clone(),
+            pollutant.index.to_string(),
+            String::new(),
+            pollutant.concentration.to_string(),
+        ]
+    }));
+    Table {
+        headers: vec!["Pollutant", "Index", "Category", "Concentration"],
+        rows,
+    }
+}
+
+fn air_quality_forecasts_table(forecasts: &[AirQuality]) -> Table {
+    Table {
+        headers: vec!["Date", "Index", "Category", "Dominant pollutant"],
+        rows: forecasts
+            .iter()
+            .map(|forecast| {
+                vec![
+                    forecast.date.clone(),
+                    forecast.overall_index.to_string(),
+                    forecast.category.clone(),
+                    forecast.dominant_pollutant.clone(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn climo_table(date: &str, day: &ClimoDay) -> Table {
+    Table {
+        headers: vec![
+            "Date",
+            "Minimum",
+            "Maximum",
+            "Average",
+            "Precipitation",
+            "Snowfall",
+        ],
+        rows: vec![vec![
+            date.to_string(),
+            day.temperatures.minimum.to_string(),
+            day.temperatures.maximum.to_string(),
+            day.temperatures.average.to_string(),
+            day.precipitation.to_string(),
+            day.snowfall.to_string(),
+        ]],
+    }
+}
+
+fn climo_summary_table(answer: &ClimoSummaryAnswer) -> Table {
+    Table {
+        headers: vec![
+            "Date",
+            "Minimum",
+            "Maximum",
+            "Normal minimum",
+            "Normal maximum",
+            "Precipitation",
+            "Normal precipitation",
+        ],
+        rows: answer
+            .actuals
+            .iter()
+            .map(|actuals| {
+                let normals = answer
+                    .normals
+                    .iter()
+                    .find(|normals| normals.date == actuals.date);
+                let normal = |value: fn(&ClimoDay) -> &AccuweatherMeasurement| {
+                    normals.map_or_else(String::new, |normals| value(&normals.normals).to_string())
+                };
+                let day = &actuals.actuals;
+                vec![
+                    actuals.date.clone(),
+                    day.temperatures.minimum.to_string(),
+                    day.temperatures.maximum.to_string(),
+                    normal(|day| &day.temperatures.minimum),
+                    normal(|day| &day.temperatures.maximum),
+                    day.precipitation.to_string(),
+                    normal(|day| &day.precipitation),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn climo_records_table(answer: &ClimoRecordsAnswer) -> Table {
+    let records = &answer.records;
+    let row = |name: &str, record: &ClimoRecord| {
+        vec![
+            name.to_string(),
+            record.measurement.to_string(),
+            record.year.to_string(),
+        ]
+    };
+    Table {
+        headers: vec!["Record", "Value", "Year"],
+        rows: vec![
+            row("Maximum temperature", &records.temperatures.maximum),
+            row("Minimum temperature", &records.temperatures.minimum),
+            row("Precipitation", &records.precipitation),
+            row("Snowfall", &records.snowfall),
+        ],
+    }
+}
+
+fn imagery_table(frames: &[ImageryFrame]) -> Table {
+    Table {
+        headers: vec!["Type", "Date", "Size", "Url"],
+        rows: frames
+            .iter()
+            .map(|frame| {
+                vec![
+                    format!("{:?}", frame.kind),
+                    frame.date.clone(),
+                    frame.size.clone(),
+                    frame.url.clone(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn format_table(table: &Table) -> String {
+    let mut widths: Vec<usize> = table.headers.iter().map(|h| h.chars().count()).collect();
+    for row in &table.rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        padded.join("  ").trim_end().to_string() + "\n"
+    };
+    let mut output = line(table.headers.clone());
+    for row in &table.rows {
+        output.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+    output
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn format_csv(table: &Table) -> String {
+    let mut output = table.headers.join(",") + "\n";
+    for row in &table.rows {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        output.push_str(&fields.join(","));
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options =
+            parse_options(args(&["-l", "623", "daily", "10", "--format", "csv"])).unwrap();
+        assert_eq!(options.location, Some(623));
+        assert!(options.format == Format::Csv);
+        assert_eq!(options.command, args(&["daily", "10"]));
+        assert_eq!(
+            parse_options(args(&["-l", "paris", "daily"]))
+                .err()
+                .unwrap()
+                .code,
+            EXIT_BAD_INPUT
+        );
+        assert!(parse_options(args(&["--format", "xml", "daily"])).is_err());
+        assert!(parse_options(args(&["-l", "623"])).is_err());
+        let error = parse_options(args(&["forecast"])).err().unwrap();
+        assert_eq!(error.code, EXIT_BAD_INPUT);
+        assert!(error.message.starts_with("unknown command forecast"));
+        let options = parse_options(args(&["air-quality", "hourly", "48"])).unwrap();
+        assert_eq!(options.command, args(&["air-quality", "hourly", "48"]));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date(Some(&"2019-05-28".to_string())).unwrap(),
+            (2019, 5, 28)
+        );
+        assert!(parse_date(Some(&"2019-05".to_string())).is_err());
+        assert_eq!(
+            parse_date(Some(&"2019-5-x".to_string()))
+                .unwrap_err()
+                .to_string(),
+            "invalid date 2019-5-x, expected YYYY-MM-DD"
+        );
+        assert_eq!(
+            parse_date(None).unwrap_err().to_string(),
+            "missing date, expected YYYY-MM-DD"
+        );
+        assert_eq!(
+            parse_month(Some(&"2019-05".to_string())).unwrap(),
+            (2019, 5)
+        );
+        assert!(parse_month(Some(&"2019-05-28".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_format_csv() {
+        let table = Table {
+            headers: vec!["Name", "Area"],
+            rows: vec![args(&["Paris", "Texas, \"US\""])],
+        };
+        assert_eq!(
+            format_csv(&table),
+            "Name,Area\nParis,\"Texas, \"\"US\"\"\"\n"
+        );
+        assert_eq!(format_table(&table), "Name   Area\nParis  Texas, \"US\"\n");
+    }
+}
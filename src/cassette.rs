@@ -0,0 +1,279 @@
+//! Recording and replaying api answers
+//!
+//! A client with a recording `Cassette` keeps each answer it reads, with its status and the
+//! headers read by `ApiResponse`, and saves them as json to the cassette file. A client with
+//! a replaying `Cassette` answers from the file instead of the network, and fails with
+//! `CassetteError::Unmatched` on a request missing from it, or sent more times than it was
+//! recorded.
+//!
+//! Every attempt of a request is kept, error statuses included, and replayed in order
+//! through the same retries and key failover. Requests are matched on their path and
+//! query, which hold no api key: the key is only added when sending the request. The
+//! answers are also scrubbed of the keys of the client. Imagery frames are kept base64
+//! encoded.
+//!
+//! # Example
+//! ```no_run
+//! use accuweather::cassette::Cassette;
+//!
+//! let mut client = accuweather::Accuweather::new("abcdefg".to_string(), Some(623), None);
+//! client.set_cassette(Some(Cassette::record("tests/cassettes/paris.json")));
+//! let conditions = client.get_current_conditions();
+//! client.save_cassette().unwrap();
+//!
+//! client.set_cassette(Some(Cassette::replay("tests/cassettes/paris.json").unwrap()));
+//! let replayed = client.get_current_conditions();
+//! ```
+use crate::transport::{header, HttpResponse};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::{error, fmt, fs};
+use url::Url;
+
+/// Headers kept with a recorded answer
+const RECORDED_HEADERS: &[&str] = &[
+    "RateLimit-Remaining",
+    "Expires",
+    "Date",
+    "X-Request-Id",
+    "X-Correlation-Id",
+];
+
+/// Error returned when a cassette can't be read or written, or misses a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CassetteError {
+    /// the cassette file can't be read, parsed or written
+    File { path: PathBuf, message: String },
+    /// a replayed request is not in the cassette, or was sent more times than recorded
+    Unmatched { request: String },
+}
+
+impl fmt::Display for CassetteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CassetteError::File { path, message } => {
+                write!(f, "invalid cassette {}: {}", path.display(), message)
+            }
+            CassetteError::Unmatched { request } => {
+                write!(
+                    f,
+                    "request {} is not in the cassette, or its answers were all replayed",
+                    request
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for CassetteError {}
+
+/// A recorded request and its answer
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// path and query of the request
+    pub request: String,
+    /// http status code of the answer
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// answer body, as text, or base64 encoded when `binary`
+    pub body: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub binary: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+/// Answers recorded to or replayed from a file
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    interactions: Mutex<Vec<Interaction>>,
+    /// whether each interaction was replayed
+    replayed: Mutex<Vec<bool>>,
+}
+
+impl Cassette {
+    /// Record the answers, to save them to `path`
+    ///
+    /// The answers are also saved when the cassette is dropped, but the write errors are
+    /// lost then: call `save` to get them.
+    pub fn record<P: AsRef<Path>>(path: P) -> Self {
+        Cassette {
+            path: path.as_ref().to_path_buf(),
+            mode: Mode::Record,
+            interactions: Mutex::new(Vec::new()),
+            replayed: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Replay the answers recorded in `path`
+    ///
+    /// Returns a Result with either the Cassette or the error of the file
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self, CassetteError> {
+        let path = path.as_ref();
+        let file_error = |message: String| CassetteError::File {
+            path: path.to_path_buf(),
+            message,
+        };
+        let text = fs::read_to_string(path).map_err(|error| file_error(error.to_string()))?;
+        let file: CassetteFile =
+            serde_json::from_str(&text).map_err(|error| file_error(error.to_string()))?;
+        Ok(Cassette {
+            path: path.to_path_buf(),
+            mode: Mode::Replay,
+            replayed: Mutex::new(vec![false; file.interactions.len()]),
+            interactions: Mutex::new(file.interactions),
+        })
+    }
+
+    /// Whether the cassette answers requests instead of the network
+    pub fn is_replaying(&self) -> bool {
+        self.mode == Mode::Replay
+    }
+
+    /// Recorded or loaded interactions
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+
+    /// Requests of the interactions not replayed yet
+    pub fn unplayed(&self) -> Vec<String> {
+        let interactions = self.interactions.lock().unwrap();
+        let replayed = self.replayed.lock().unwrap();
+        interactions
+            .iter()
+            .zip(replayed.iter())
+            .filter(|(_, &replayed)| !replayed)
+            .map(|(interaction, _)| interaction.request.clone())
+            .collect()
+    }
+
+    /// Write the interactions to the cassette file, creating its directory
+    pub fn save(&self) -> Result<(), CassetteError> {
+        let file_error = |message: String| CassetteError::File {
+            path: self.path.clone(),
+            message,
+        };
+        let file = CassetteFile {
+            interactions: self.interactions(),
+        };
+        let text =
+            serde_json::to_string_pretty(&file).map_err(|error| file_error(error.to_string()))?;
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory).map_err(|error| file_error(error.to_string()))?;
+        }
+        fs::write(&self.path, text).map_err(|error| file_error(error.to_string()))
+    }
+
+    /// Keep an answer when recording, its text already scrubbed of api keys
+    pub(crate) fn record_answer(
+        &self,
+        url: &Url,
+        status: u16,
+        headers: &[(String, String)],
+        body: &str,
+    ) {
+        self.push_interaction(url, status, headers, body.to_string(), false);
+    }
+
+    /// Keep an answer with a binary body when recording, such as an image
+    pub(crate) fn record_binary_answer(&self, url: &Url, response: &HttpResponse) {
+        let body = BASE64.encode(&response.body);
+        self.push_interaction(url, response.status, &response.headers, body, true);
+    }
+
+    fn push_interaction(
+        &self,
+        url: &Url,
+        status: u16,
+        headers: &[(String, String)],
+        body: String,
+        binary: bool,
+    ) {
+        if self.mode != Mode::Record {
+            return;
+        }
+        let headers = RECORDED_HEADERS
+            .iter()
+            .filter_map(|&name| {
+                let value = header(headers, name)?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect();
+        self.interactions.lock().unwrap().push(Interaction {
+            request: request_key(url),
+            status,
+            headers,
+            body,
+            binary,
+        });
+        self.replayed.lock().unwrap().push(false);
+    }
+
+    /// The answer to `url`
+    ///
+    /// Interactions of a request are replayed once each, in their recorded order.
+    pub(crate) fn replay_answer(&self, url: &Url) -> Result<HttpResponse, CassetteError> {
+        let request = request_key(url);
+        let interactions = self.interactions.lock().unwrap();
+        let mut replayed = self.replayed.lock().unwrap();
+        let index = (0..interactions.len())
+            .find(|&i| interactions[i].request == request && !replayed[i])
+            .ok_or(CassetteError::Unmatched { request })?;
+        replayed[index] = true;
+        let interaction = &interactions[index];
+        let body = if interaction.binary {
+            BASE64
+                .decode(&interaction.body)
+                .map_err(|error| CassetteError::File {
+                    path: self.path.clone(),
+                    message: format!("{}: {}", interaction.request, error),
+                })?
+        } else {
+            interaction.body.clone().into_bytes()
+        };
+        Ok(HttpResponse {
+            status: interaction.status,
+            headers: interaction
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            body,
+        })
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        // errors are lost here, `save` returns them
+        if self.mode == Mode::Record && !self.interactions.lock().unwrap().is_empty() {
+            let _ = self.save();
+        }
+    }
+}
+
+/// Path and query of a request url, which hold no credential
+fn request_key(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
@@ -0,0 +1,260 @@
+//! Client configuration from environment variables and TOML files
+//!
+//! Environment variables are named after the settings of `Config`, uppercased and
+//! prefixed with `ACCUWEATHER_`: `ACCUWEATHER_API_KEY`, `ACCUWEATHER_LOCATION`,
+//! `ACCUWEATHER_LANGUAGE`, `ACCUWEATHER_UNITS`, `ACCUWEATHER_BASE_URL`,
+//! `ACCUWEATHER_SUBSCRIPTION_TIER`, `ACCUWEATHER_AUTH_MODE`, `ACCUWEATHER_LENIENT`,
+//! `ACCUWEATHER_TIMEOUT_SECS`, `ACCUWEATHER_RETRIES`, `ACCUWEATHER_PARALLELISM`, `ACCUWEATHER_FALLBACK_API_KEYS`
+//! (comma separated) and `ACCUWEATHER_KEY_STRATEGY`.
+//!
+//! A config file holds the same settings, lowercased:
+//! ```toml
+//! api_key = "abcdefg"
+//! location = 12345
+//! language = "fr-fr"
+//! units = "metric"
+//! subscription_tier = "standard"
+//! auth_mode = "header"
+//! timeout_secs = 10
+//! retries = 2
+//! parallelism = 8
+//! fallback_api_keys = ["hijklmn"]
+//! key_strategy = "primary_with_fallback"
+//! ```
+use crate::auth::{ApiKey, AuthMode, KeyStrategy};
+use crate::periods::SubscriptionTier;
+use crate::Accuweather;
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, error, fmt, fs};
+use url::Url;
+
+const ENV_PREFIX: &str = "ACCUWEATHER_";
+
+/// Unit system of forecast and climatology values
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Units {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl Units {
+    /// Value of the `metric` query parameter
+    pub(crate) fn metric_parameter(self) -> &'static str {
+        match self {
+            Units::Metric => "true",
+            Units::Imperial => "false",
+        }
+    }
+}
+
+/// Error returned when a configuration can't be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// a required setting is missing
+    Missing(String),
+    /// a setting has an invalid value
+    Invalid { setting: String, message: String },
+    /// the config file can't be read or parsed
+    File { path: PathBuf, message: String },
+    /// the config text isn't valid TOML
+    Parse(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Missing(setting) => write!(f, "missing setting {}", setting),
+            ConfigError::Invalid { setting, message } => {
+                write!(f, "invalid setting {}: {}", setting, message)
+            }
+            ConfigError::File { path, message } => {
+                write!(f, "invalid config file {}: {}", path.display(), message)
+            }
+            ConfigError::Parse(message) => write!(f, "invalid config: {}", message),
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
+
+/// Settings of an Accuweather client, unset ones keeping the client defaults
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub api_key: Option<ApiKey>,
+    pub location: Option<i32>,
+    pub language: Option<String>,
+    pub units: Option<Units>,
+    pub base_url: Option<String>,
+    pub subscription_tier: Option<SubscriptionTier>,
+    pub auth_mode: Option<AuthMode>,
+    pub lenient: Option<bool>,
+    pub timeout_secs: Option<u64>,
+    pub retries: Option<u32>,
+    pub parallelism: Option<usize>,
+    /// keys used along `api_key`, as chosen by `key_strategy`
+    pub fallback_api_keys: Option<Vec<ApiKey>>,
+    pub key_strategy: Option<KeyStrategy>,
+}
+
+impl Config {
+    /// Read the configuration from `ACCUWEATHER_*` environment variables
+    ///
+    /// Returns a Result with either the Config or the error naming the missing or invalid variable
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Config::from_lookup(|name| env::var(name).ok())
+    }
+
+    /// Read the configuration from a TOML file
+    ///
+    /// Returns a Result with either the Config or the error naming the missing or invalid setting
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|error| ConfigError::File {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })?;
+        Config::from_toml(&text).map_err(|error| match error {
+            ConfigError::Parse(message) => ConfigError::File {
+                path: path.to_path_buf(),
+                message,
+            },
+            error => error,
+        })
+    }
+
+    /// Read the configuration from TOML text
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        let mut deserializer = toml::Deserializer::new(text);
+        let config: Config =
+            serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+                let setting = error.path().to_string();
+                let message = error.inner().to_string();
+                if setting == "." {
+                    ConfigError::Parse(message)
+                } else {
+                    ConfigError::Invalid { setting, message }
+                }
+            })?;
+        if config.api_key.is_none() {
+            return Err(ConfigError::Missing("api_key".to_string()));
+        }
+        if let Some(base_url) = &config.base_url {
+            check_url("base_url", base_url)?;
+        }
+        Ok(config)
+    }
+
+    pub(crate) fn from_lookup<F: Fn(&str) -> Option<String>>(
+        lookup: F,
+    ) -> Result<Self, ConfigError> {
+        let var = |setting: &str| {
+            let name = format!("{}{}", ENV_PREFIX, setting.to_uppercase());
+            lookup(&name).map(|value| (name, value))
+        };
+        let api_key = match var("api_key") {
+            Some((_, key)) => ApiKey::from(key),
+            None => return Err(ConfigError::Missing(format!("{}API_KEY", ENV_PREFIX))),
+        };
+        Ok(Config {
+            api_key: Some(api_key),
+            location: var("location").map(parse).transpose()?,
+            language: var("language").map(|(_, value)| value),
+            units: var("units").map(parse_enum).transpose()?,
+            base_url: var("base_url")
+                .map(|(name, value)| check_url(&name, &value).map(|()| value))
+                .transpose()?,
+            subscription_tier: var("subscription_tier").map(parse_enum).transpose()?,
+            auth_mode: var("auth_mode").map(parse_enum).transpose()?,
+            lenient: var("lenient").map(parse).transpose()?,
+            timeout_secs: var("timeout_secs").map(parse).transpose()?,
+            retries: var("retries").map(parse).transpose()?,
+            parallelism: var("parallelism").map(parse).transpose()?,
+            fallback_api_keys: var("fallback_api_keys").map(|(_, value)| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(ApiKey::from)
+                    .collect()
+            }),
+            key_strategy: var("key_strategy").map(parse_enum).transpose()?,
+        })
+    }
+
+    /// Build an Accuweather client from the configuration
+    ///
+    /// Returns a Result with either the client or the error naming the missing or invalid setting
+    pub fn build(self) -> Result<Accuweather, ConfigError> {
+        let api_key = self
+            .api_key
+            .ok_or_else(|| ConfigError::Missing("api_key".to_string()))?;
+        let mut client =
+            Accuweather::new(api_key.expose().to_string(), self.location, self.language);
+        if self.fallback_api_keys.is_some() || self.key_strategy.is_some() {
+            let mut keys = vec![api_key];
+            keys.extend(self.fallback_api_keys.unwrap_or_default());
+            // can't fail, the pool holds at least api_key
+            let _ = client.set_api_keys(keys, self.key_strategy.unwrap_or_default());
+        }
+        if let Some(units) = self.units {
+            client.set_units(units);
+        }
+        if let Some(base_url) = self.base_url {
+            check_url("base_url", &base_url)?;
+            client.set_base_url(base_url);
+        }
+        if let Some(tier) = self.subscription_tier {
+            client.set_subscription_tier(tier);
+        }
+        if let Some(auth_mode) = self.auth_mode {
+            client.set_auth_mode(auth_mode);
+        }
+        if let Some(lenient) = self.lenient {
+            client.set_lenient(lenient);
+        }
+        if let Some(timeout) = self.timeout_secs {
+            client.set_timeout(Some(Duration::from_secs(timeout)));
+        }
+        if let Some(retries) = self.retries {
+            client.set_retries(retries);
+        }
+        if let Some(parallelism) = self.parallelism {
+            client.set_parallelism(parallelism);
+        }
+        Ok(client)
+    }
+}
+
+fn parse<T>((name, value): (String, String)) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|error: T::Err| ConfigError::Invalid {
+        setting: name,
+        message: error.to_string(),
+    })
+}
+
+fn check_url(setting: &str, url: &str) -> Result<(), ConfigError> {
+    Url::parse(url).map_err(|error| ConfigError::Invalid {
+        setting: setting.to_string(),
+        message: error.to_string(),
+    })?;
+    Ok(())
+}
+
+fn parse_enum<T: DeserializeOwned>((name, value): (String, String)) -> Result<T, ConfigError> {
+    let deserializer: serde::de::value::StrDeserializer<'_, serde::de::value::Error> =
+        value.as_str().into_deserializer();
+    T::deserialize(deserializer).map_err(|error| ConfigError::Invalid {
+        setting: name,
+        message: error.to_string(),
+    })
+}
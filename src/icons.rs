@@ -0,0 +1,269 @@
+//! Accuweather weather icons
+//!
+//! Every forecast and condition carries an icon number between 1 and 44 (9, 10, 27 and 28
+//! are unused). `WeatherIcon` gives them names and maps them to categories, emojis,
+//! the official icon images and WMO present weather codes.
+use crate::AccuweatherInvalidParameterError;
+use std::convert::TryFrom;
+
+/// Category of weather an icon belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IconCategory {
+    Clear,
+    Cloudy,
+    Rain,
+    Snow,
+    Mixed,
+    Thunderstorm,
+    Fog,
+    Wind,
+}
+
+/// Accuweather weather icon
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "i32", into = "i32")]
+#[repr(i32)]
+pub enum WeatherIcon {
+    Sunny = 1,
+    MostlySunny = 2,
+    PartlySunny = 3,
+    IntermittentClouds = 4,
+    HazySunshine = 5,
+    MostlyCloudy = 6,
+    Cloudy = 7,
+    Dreary = 8,
+    Fog = 11,
+    Showers = 12,
+    MostlyCloudyWithShowers = 13,
+    PartlySunnyWithShowers = 14,
+    Thunderstorms = 15,
+    MostlyCloudyWithThunderstorms = 16,
+    PartlySunnyWithThunderstorms = 17,
+    Rain = 18,
+    Flurries = 19,
+    MostlyCloudyWithFlurries = 20,
+    PartlySunnyWithFlurries = 21,
+    Snow = 22,
+    MostlyCloudyWithSnow = 23,
+    Ice = 24,
+    Sleet = 25,
+    FreezingRain = 26,
+    RainAndSnow = 29,
+    Hot = 30,
+    Cold = 31,
+    Windy = 32,
+    Clear = 33,
+    MostlyClear = 34,
+    PartlyCloudy = 35,
+    IntermittentCloudsNight = 36,
+    HazyMoonlight = 37,
+    MostlyCloudyNight = 38,
+    PartlyCloudyWithShowers = 39,
+    MostlyCloudyWithShowersNight = 40,
+    PartlyCloudyWithThunderstorms = 41,
+    MostlyCloudyWithThunderstormsNight = 42,
+    MostlyCloudyWithFlurriesNight = 43,
+    MostlyCloudyWithSnowNight = 44,
+}
+
+use self::WeatherIcon::*;
+
+const ICONS: [WeatherIcon; 40] = [
+    Sunny,
+    MostlySunny,
+    PartlySunny,
+    IntermittentClouds,
+    HazySunshine,
+    MostlyCloudy,
+    Cloudy,
+    Dreary,
+    Fog,
+    Showers,
+    MostlyCloudyWithShowers,
+    PartlySunnyWithShowers,
+    Thunderstorms,
+    MostlyCloudyWithThunderstorms,
+    PartlySunnyWithThunderstorms,
+    Rain,
+    Flurries,
+    MostlyCloudyWithFlurries,
+    PartlySunnyWithFlurries,
+    Snow,
+    MostlyCloudyWithSnow,
+    Ice,
+    Sleet,
+    FreezingRain,
+    RainAndSnow,
+    Hot,
+    Cold,
+    Windy,
+    Clear,
+    MostlyClear,
+    PartlyCloudy,
+    IntermittentCloudsNight,
+    HazyMoonlight,
+    MostlyCloudyNight,
+    PartlyCloudyWithShowers,
+    MostlyCloudyWithShowersNight,
+    PartlyCloudyWithThunderstorms,
+    MostlyCloudyWithThunderstormsNight,
+    MostlyCloudyWithFlurriesNight,
+    MostlyCloudyWithSnowNight,
+];
+
+impl TryFrom<i32> for WeatherIcon {
+    type Error = AccuweatherInvalidParameterError;
+
+    fn try_from(number: i32) -> Result<Self, Self::Error> {
+        ICONS
+            .iter()
+            .find(|icon| **icon as i32 == number)
+            .copied()
+            .ok_or_else(|| {
+                AccuweatherInvalidParameterError::new(
+                    "icon",
+                    number,
+                    "1 to 44, except 9, 10, 27 and 28",
+                )
+            })
+    }
+}
+
+impl From<WeatherIcon> for i32 {
+    fn from(icon: WeatherIcon) -> i32 {
+        icon as i32
+    }
+}
+
+impl WeatherIcon {
+    /// Accuweather icon number
+    pub fn number(self) -> i32 {
+        self as i32
+    }
+
+    /// Whether the icon may be used during the day
+    pub fn is_day(self) -> bool {
+        self.number() <= 32
+    }
+
+    /// Whether the icon may be used during the night
+    pub fn is_night(self) -> bool {
+        match self {
+            Cloudy | Dreary | Fog | Showers | Thunderstorms | Rain | Flurries | Snow | Ice
+            | Sleet | FreezingRain | RainAndSnow | Hot | Cold | Windy => true,
+            _ => self.number() >= 33,
+        }
+    }
+
+    /// Category of weather shown by the icon
+    pub fn category(self) -> IconCategory {
+        match self {
+            Sunny | MostlySunny | HazySunshine | Hot | Cold | Clear | MostlyClear
+            | HazyMoonlight => IconCategory::Clear,
+            PartlySunny
+            | IntermittentClouds
+            | MostlyCloudy
+            | Cloudy
+            | Dreary
+            | PartlyCloudy
+            | IntermittentCloudsNight
+            | MostlyCloudyNight => IconCategory::Cloudy,
+            Fog => IconCategory::Fog,
+            Showers
+            | MostlyCloudyWithShowers
+            | PartlySunnyWithShowers
+            | Rain
+            | PartlyCloudyWithShowers
+            | MostlyCloudyWithShowersNight => IconCategory::Rain,
+            Thunderstorms
+            | MostlyCloudyWithThunderstorms
+            | PartlySunnyWithThunderstorms
+            | PartlyCloudyWithThunderstorms
+            | MostlyCloudyWithThunderstormsNight => IconCategory::Thunderstorm,
+            Flurries
+            | MostlyCloudyWithFlurries
+            | PartlySunnyWithFlurries
+            | Snow
+            | MostlyCloudyWithSnow
+            | MostlyCloudyWithFlurriesNight
+            | MostlyCloudyWithSnowNight => IconCategory::Snow,
+            Ice | Sleet | FreezingRain | RainAndSnow => IconCategory::Mixed,
+            Windy => IconCategory::Wind,
+        }
+    }
+
+    /// Emoji close to the icon
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Sunny => "☀️",
+            MostlySunny | HazySunshine => "🌤️",
+            PartlySunny | IntermittentClouds => "⛅",
+            MostlyCloudy => "🌥️",
+            Cloudy | Dreary | PartlyCloudy | IntermittentCloudsNight | MostlyCloudyNight => "☁️",
+            Fog => "🌫️",
+            MostlyCloudyWithShowers | PartlySunnyWithShowers => "🌦️",
+            Showers
+            | Rain
+            | FreezingRain
+            | PartlyCloudyWithShowers
+            | MostlyCloudyWithShowersNight => "🌧️",
+            Thunderstorms
+            | MostlyCloudyWithThunderstorms
+            | PartlySunnyWithThunderstorms
+            | PartlyCloudyWithThunderstorms
+            | MostlyCloudyWithThunderstormsNight => "⛈️",
+            Flurries
+            | MostlyCloudyWithFlurries
+            | PartlySunnyWithFlurries
+            | Sleet
+            | RainAndSnow
+            | MostlyCloudyWithFlurriesNight => "🌨️",
+            Snow | MostlyCloudyWithSnow | MostlyCloudyWithSnowNight => "❄️",
+            Ice => "🧊",
+            Hot => "🥵",
+            Cold => "🥶",
+            Windy => "💨",
+            Clear | MostlyClear | HazyMoonlight => "🌙",
+        }
+    }
+
+    /// Url of the icon image on accuweather developer website
+    pub fn image_url(self) -> String {
+        format!(
+            "https://developer.accuweather.com/sites/default/files/{:02}-s.png",
+            self.number()
+        )
+    }
+
+    /// Closest WMO present weather code (code table 4677)
+    pub fn wmo_code(self) -> u8 {
+        match self {
+            Sunny | Hot | Cold | Clear => 0,
+            MostlySunny | MostlyClear => 1,
+            PartlySunny | IntermittentClouds | PartlyCloudy | IntermittentCloudsNight => 2,
+            MostlyCloudy | Cloudy | Dreary | MostlyCloudyNight => 3,
+            HazySunshine | HazyMoonlight => 5,
+            Windy => 18,
+            Fog => 45,
+            Rain => 63,
+            FreezingRain => 66,
+            RainAndSnow => 68,
+            Snow | MostlyCloudyWithSnow | MostlyCloudyWithSnowNight => 73,
+            Ice | Sleet => 79,
+            Showers
+            | MostlyCloudyWithShowers
+            | PartlySunnyWithShowers
+            | PartlyCloudyWithShowers
+            | MostlyCloudyWithShowersNight => 80,
+            Flurries
+            | MostlyCloudyWithFlurries
+            | PartlySunnyWithFlurries
+            | MostlyCloudyWithFlurriesNight => 85,
+            Thunderstorms
+            | MostlyCloudyWithThunderstorms
+            | PartlySunnyWithThunderstorms
+            | PartlyCloudyWithThunderstorms
+            | MostlyCloudyWithThunderstormsNight => 95,
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Lenient parsing of api answers
+//!
+//! In lenient mode a field that fails to parse (unexpected `null`, wrong type...) is
+//! replaced by `null` and parsing is retried, so that optional fields end up as `None`
+//! instead of failing the whole answer. When the field can't be `null` its enclosing
+//! object is dropped instead, up to the first optional one. Each tolerated failure is
+//! recorded as a `LenientIssue`.
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+
+/// A field that failed to parse and was dropped in lenient mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LenientIssue {
+    /// path of the field that failed to parse, e.g. `[0].Ceiling.Value`
+    pub path: String,
+    /// the parse error
+    pub message: String,
+    /// path of the value replaced by `null`, `path` itself or one of its parents
+    pub dropped: String,
+}
+
+impl fmt::Display for LenientIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (dropped {})",
+            self.path, self.message, self.dropped
+        )
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Key {
+    Index(usize),
+    Field(String),
+}
+
+fn keys(path: &serde_path_to_error::Path) -> Vec<Key> {
+    let mut keys = Vec::new();
+    for segment in path.iter() {
+        match segment {
+            serde_path_to_error::Segment::Seq { index } => keys.push(Key::Index(*index)),
+            serde_path_to_error::Segment::Map { key } => keys.push(Key::Field(key.clone())),
+            _ => break,
+        }
+    }
+    keys
+}
+
+fn display(keys: &[Key]) -> String {
+    let mut path = String::new();
+    for key in keys {
+        match key {
+            Key::Index(index) => path.push_str(&format!("[{}]", index)),
+            Key::Field(field) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(field)
+            }
+        }
+    }
+    if path.is_empty() {
+        ".".to_string()
+    } else {
+        path
+    }
+}
+
+fn lookup<'a>(value: &'a mut Value, keys: &[Key]) -> Option<&'a mut Value> {
+    keys.iter().try_fold(value, |value, key| match key {
+        Key::Index(index) => value.get_mut(*index),
+        Key::Field(field) => value.get_mut(field.as_str()),
+    })
+}
+
+/// Parse `value`, dropping fields that fail to parse.
+///
+/// Returns the parsed value and the tolerated issues, or the first parse error when
+/// dropping fields doesn't help.
+pub(crate) fn parse<T: DeserializeOwned>(
+    mut value: Value,
+) -> Result<(T, Vec<LenientIssue>), serde_json::Error> {
+    let mut issues: Vec<LenientIssue> = Vec::new();
+    let mut first_error = None;
+    let mut last_dropped: Option<Vec<Key>> = None;
+    loop {
+        let err = match serde_path_to_error::deserialize(&value) {
+            Ok(parsed) => return Ok((parsed, issues)),
+            Err(err) => err,
+        };
+        let path = keys(err.path());
+        let mut target = if last_dropped.as_ref() == Some(&path) {
+            // nulling the field wasn't accepted, drop its parent
+            path[..path.len() - 1].to_vec()
+        } else {
+            path.clone()
+        };
+        while !target.is_empty() && matches!(lookup(&mut value, &target), None | Some(Value::Null))
+        {
+            target.pop();
+        }
+        if target.is_empty() {
+            return Err(first_error.unwrap_or_else(|| err.into_inner()));
+        }
+        *lookup(&mut value, &target).unwrap() = Value::Null;
+        if last_dropped.as_ref() == Some(&path) {
+            if let Some(issue) = issues.last_mut() {
+                issue.dropped = display(&target);
+            }
+        } else {
+            issues.push(LenientIssue {
+                path: display(&path),
+                message: err.inner().to_string(),
+                dropped: display(&target),
+            });
+        }
+        last_dropped = Some(target);
+        if first_error.is_none() {
+            first_error = Some(err.into_inner());
+        }
+    }
+}
@@ -1,6 +1,5 @@
 //! # Accuweather a crate to interact with accuweather api
-//! This crate provides a client to accuweather forecast and current conditions api.
-//! At the moment there is only three functions to interact with the api
+//! This crate provides a client to accuweather forecast, current conditions, air quality, climatology and imagery api.
 //!
 //! # Example
 //! ```
@@ -10,33 +9,103 @@
 //! let api_key = "abcdefg".to_string();
 //! let client = accuweather::Accuweather::new(api_key, Some(12345), None);
 //! // get next 12 hours of hourly forecasts
-//! let hourly_forecasts = client.get_hourly_forecasts(12);
-//! 
-//! let daily_forecasts = client.get_daily_forecasts(5);
+//! let hourly_forecasts = client.get_hourly_forecasts(accuweather::periods::HourlyPeriod::Hours12);
+//!
+//! let daily_forecasts = client.get_daily_forecasts(accuweather::periods::DailyPeriod::Days5);
 //! let conditions = client.get_current_conditions();
+//! let air_quality = client.get_air_quality_current_conditions();
+//! // compare today's forecast with climatological normals
+//! let normals = client.get_climo_normals(2019, 5, 28);
+//! let radar_loop = client.get_radar_frames("480x480");
+//! ```
 
-
-
-extern crate reqwest;
 #[macro_use]
 extern crate serde_derive;
 
+use crate::auth::{ApiKey, AuthMode, KeyPool, KeyState, KeyStrategy, KeyUsage};
+use crate::cassette::{Cassette, CassetteError};
+use crate::config::{Config, ConfigError, Units};
+use crate::lenient::LenientIssue;
+use crate::periods::*;
+use crate::response::ApiResponse;
+use crate::schedule::{Answer, Endpoint};
+use crate::transport::{AsyncTransport, HttpRequest, HttpResponse, Transport, TransportError};
 use crate::types::*;
-use reqwest::Client;
-use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::error;
 use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use url::Url;
 
+pub mod auth;
+pub mod cassette;
+pub mod config;
+pub mod icons;
+pub mod lenient;
+pub mod periods;
+pub mod provider;
+pub mod response;
+pub mod schedule;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
 pub mod types;
+pub mod watch;
 
 type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 
-#[derive(Debug, Clone)]
-pub struct AccuweatherInvalidParameterError;
+/// Result whose error can be sent between threads, as returned by batch methods
+pub type SendResult<T> = std::result::Result<T, Box<dyn error::Error + Send + Sync>>;
+
+type ParameterResult<T> = std::result::Result<T, AccuweatherInvalidParameterError>;
+
+/// Drop the Send and Sync bounds of an error
+fn unsend(error: Box<dyn error::Error + Send + Sync>) -> Box<dyn error::Error> {
+    error
+}
+
+/// Error returned when a parameter is rejected, before any request is sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccuweatherInvalidParameterError {
+    /// name of the parameter, e.g. "hourly period"
+    pub parameter: &'static str,
+    /// the rejected value
+    pub value: String,
+    /// the accepted values
+    pub allowed: String,
+}
+
+impl AccuweatherInvalidParameterError {
+    pub fn new<V: fmt::Display, A: Into<String>>(
+        parameter: &'static str,
+        value: V,
+        allowed: A,
+    ) -> Self {
+        AccuweatherInvalidParameterError {
+            parameter,
+            value: value.to_string(),
+            allowed: allowed.into(),
+        }
+    }
+}
 
 impl fmt::Display for AccuweatherInvalidParameterError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "invalid first item to double")
+        if self.value.is_empty() {
+            write!(f, "missing {}, expected {}", self.parameter, self.allowed)
+        } else {
+            write!(
+                f,
+                "invalid {} {}, expected {}",
+                self.parameter, self.value, self.allowed
+            )
+        }
     }
 }
 
@@ -50,13 +119,130 @@ impl error::Error for AccuweatherInvalidParameterError {
     }
 }
 
+/// Error returned when an api answer can't be parsed, with the answer
+#[derive(Debug)]
+pub struct AccuweatherParseError {
+    /// the json answer as parsed by serde_json
+    pub payload: Value,
+    error: serde_json::Error,
+}
+
+impl fmt::Display for AccuweatherParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid answer: {}", self.error)
+    }
+}
+
+impl error::Error for AccuweatherParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Error returned when the api can't be reached or answers with an error status
+#[derive(Debug)]
+pub struct AccuweatherHttpError {
+    /// http status code of the answer, if any
+    pub status: Option<u16>,
+    message: String,
+}
+
+impl fmt::Display for AccuweatherHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl error::Error for AccuweatherHttpError {}
+
+/// Error of an answer body that is not json
+fn body_error(error: serde_json::Error) -> AccuweatherHttpError {
+    AccuweatherHttpError {
+        status: None,
+        message: error.to_string(),
+    }
+}
+
+/// New state of the key of a request the api refused, if it did
+///
+/// Reads the answer body of 503 errors, to find whether the key quota is exceeded.
+fn key_rejection(response: &HttpResponse) -> Option<KeyState> {
+    match response.status {
+        status @ 401 | status @ 403 => Some(KeyState::Rejected(status)),
+        503 => {
+            if response.text().contains("exceeded") {
+                Some(KeyState::Exhausted {
+                    since: SystemTime::now(),
+                })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Time to wait before retrying a request for the `attempt`th time, counting from 0
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_millis(200 << attempt)
+}
+
+/// Error returned by batch methods for the locations left when every api key is out of quota
+#[derive(Debug, Clone)]
+pub struct AccuweatherQuotaError;
+
+impl fmt::Display for AccuweatherQuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the daily quota of every api key is exceeded")
+    }
+}
+
+impl error::Error for AccuweatherQuotaError {}
+
+/// What to do after an attempt of a request
+enum Outcome {
+    Done(SendResult<ApiResponse<Value>>),
+    /// the key was refused, try the next one
+    Failover(AccuweatherHttpError),
+    /// the error may not happen again, retry if allowed
+    Retry(AccuweatherHttpError),
+}
+
+/// Path and parameters of an api request
+struct Query {
+    path: String,
+    params: Vec<(&'static str, String)>,
+}
+
+impl Query {
+    fn new(path: String, params: &[(&'static str, &str)]) -> Self {
+        Query {
+            path,
+            params: params
+                .iter()
+                .map(|&(key, value)| (key, value.to_string()))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Accuweather {
-    pub client: Client,
-    pub api_key: String,
+    transport: Box<dyn Transport>,
+    async_transport: Box<dyn AsyncTransport>,
+    timeout: Option<Duration>,
+    keys: KeyPool,
     pub location: Option<i32>,
     pub language: String,
+    pub subscription_tier: SubscriptionTier,
+    pub auth_mode: AuthMode,
+    pub units: Units,
+    pub retries: u32,
+    pub parallelism: usize,
+    pub lenient: bool,
+    lenient_issues: Mutex<Vec<LenientIssue>>,
     base_url: String,
+    cassette: Option<Cassette>,
 }
 
 impl Accuweather {
@@ -65,32 +251,52 @@ impl Accuweather {
     /// It takes as parameters:
     /// * api_key: a String with you api key for Accuweather
     /// * location: An optional id specifying the location to get weather from
-    /// #Example
+    ///
+    /// # Example
     /// ```
-    /// fn main() {
-    ///    let api_key = "abcdefg".to_string();
-    ///    let client = accuweather::Accuweather::new(api_key, None, None);
-    /// }
+    /// let api_key = "abcdefg".to_string();
+    /// let client = accuweather::Accuweather::new(api_key, None, None);
     /// ```
-
     pub fn new(api_key: String, location: Option<i32>, language: Option<String>) -> Self {
-        #[cfg(not(test))]
-        let url = "http://dataservice.accuweather.com";
-        #[cfg(test)]
-        let url = &mockito::server_url();
         let language = match language {
             Some(l) => l,
             None => "en-us".to_string(),
         };
         Accuweather {
-            api_key,
+            keys: KeyPool::new(vec![api_key.into()], KeyStrategy::default()),
             location,
             language,
-            client: reqwest::Client::builder().build().unwrap(),
-            base_url: url.to_string(),
+            subscription_tier: SubscriptionTier::default(),
+            auth_mode: AuthMode::default(),
+            units: Units::default(),
+            retries: 0,
+            parallelism: 4,
+            lenient: false,
+            lenient_issues: Mutex::new(Vec::new()),
+            transport: transport::default_transport(),
+            async_transport: transport::default_async_transport(),
+            timeout: Some(Duration::from_secs(30)),
+            base_url: "http://dataservice.accuweather.com".to_string(),
+            cassette: None,
         }
     }
 
+    /// Create an Accuweather client from `ACCUWEATHER_*` environment variables
+    ///
+    /// See the `config` module for the variables read.
+    /// Returns a Result with either the client or the error naming the missing or invalid variable
+    pub fn from_env() -> std::result::Result<Self, ConfigError> {
+        Config::from_env()?.build()
+    }
+
+    /// Create an Accuweather client from a TOML config file
+    ///
+    /// See the `config` module for the settings read.
+    /// Returns a Result with either the client or the error naming the missing or invalid setting
+    pub fn from_config_file<P: AsRef<Path>>(path: P) -> std::result::Result<Self, ConfigError> {
+        Config::from_file(path)?.build()
+    }
+
     /// Set location for an Accuweather client
     ///
     /// Take an Option<i32> to specify location id
@@ -105,90 +311,312 @@ impl Accuweather {
         self.location = location;
     }
 
+    /// Set the subscription tier of the api key
+    ///
+    /// Forecast periods the tier doesn't allow are rejected before any request is sent.
+    /// Defaults to SubscriptionTier::Standard.
+    /// # Example
+    /// ```
+    ///  use accuweather::periods::{HourlyPeriod, SubscriptionTier};
+    ///  let api_key = "abcdefg".to_string();
+    ///  let mut client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.set_subscription_tier(SubscriptionTier::Limited);
+    ///  assert!(client.get_hourly_forecasts(HourlyPeriod::Hours24).is_err());
+    /// ```
+    pub fn set_subscription_tier(&mut self, tier: SubscriptionTier) {
+        self.subscription_tier = tier;
+    }
+
+    /// Set how the api key is sent to the api
+    ///
+    /// Defaults to AuthMode::QueryParameter. With AuthMode::Header the key is sent in an
+    /// `Authorization` header and never appears in request urls.
+    /// # Example
+    /// ```
+    ///  use accuweather::auth::AuthMode;
+    ///  let api_key = "abcdefg".to_string();
+    ///  let mut client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.set_auth_mode(AuthMode::Header);
+    /// ```
+    pub fn set_auth_mode(&mut self, auth_mode: AuthMode) {
+        self.auth_mode = auth_mode;
+    }
+
+    /// Set lenient parsing of api answers
+    ///
+    /// When enabled, fields failing to parse are dropped instead of failing the whole answer:
+    /// optional fields become `None`. Dropped fields are recorded and can be retrieved with
+    /// `take_lenient_issues`.
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let mut client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.set_lenient(true);
+    ///  client.get_current_conditions();
+    ///  for issue in client.take_lenient_issues() {
+    ///      println!("{}", issue);
+    ///  }
+    /// ```
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Set the api keys of the client and how each request chooses one
+    ///
+    /// Requests fail over to another key when the api refuses a key (401 or 403) or answers
+    /// that its daily quota is exceeded (503). `key_usage` gives the usage and state of each key.
+    /// Returns a Result with either nothing or an AccuweatherInvalidParameterError when `keys` is empty
+    /// # Example
+    /// ```
+    ///  use accuweather::auth::{ApiKey, KeyStrategy};
+    ///  let api_key = "abcdefg".to_string();
+    ///  let mut client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  let keys = vec![ApiKey::from("abcdefg"), ApiKey::from("hijklmn")];
+    ///  client.set_api_keys(keys, KeyStrategy::RoundRobin).unwrap();
+    ///  client.get_current_conditions();
+    ///  for usage in client.key_usage() {
+    ///      println!("{}: {} requests, {:?}", usage.key, usage.requests, usage.state);
+    ///  }
+    /// ```
+    pub fn set_api_keys(&mut self, keys: Vec<ApiKey>, strategy: KeyStrategy) -> Result<()> {
+        if keys.is_empty() {
+            return Err(AccuweatherInvalidParameterError::new(
+                "api keys",
+                "[]",
+                "at least one key",
+            )
+            .into());
+        }
+        self.keys = KeyPool::new(keys, strategy);
+        Ok(())
+    }
+
+    /// Get the usage and state of each api key, in the order they were set
+    pub fn key_usage(&self) -> Vec<KeyUsage> {
+        self.keys.usage()
+    }
+
+    /// Mark every api key as active again
+    pub fn reset_key_states(&self) {
+        self.keys.reset_states();
+    }
+
+    /// Set the unit system of forecast and climatology values
+    ///
+    /// Defaults to Units::Metric. Current conditions always carry both.
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+
+    /// Set the root url of the api
+    ///
+    /// Defaults to `http://dataservice.accuweather.com`.
+    pub fn set_base_url(&mut self, base_url: String) {
+        self.base_url = base_url;
+    }
+
+    /// Record the api answers to a cassette, or answer from it without network
+    ///
+    /// See the `cassette` module. Replacing a recording cassette saves it.
+    pub fn set_cassette(&mut self, cassette: Option<Cassette>) {
+        self.cassette = cassette;
+    }
+
+    /// The cassette of the client, if any
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_ref()
+    }
+
+    /// Save the answers recorded by the cassette of the client to its file
+    ///
+    /// Returns a Result with either nothing or the error writing the file
+    pub fn save_cassette(&self) -> std::result::Result<(), CassetteError> {
+        match &self.cassette {
+            Some(cassette) => cassette.save(),
+            None => Ok(()),
+        }
+    }
+
+    /// Set the timeout of api requests
+    ///
+    /// Defaults to 30 seconds, None disables it.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Set the transport sending the http requests of the client
+    ///
+    /// Defaults to `ReqwestTransport`, or `UreqTransport` without the `reqwest-blocking`
+    /// feature. See the `transport` module.
+    pub fn set_transport<T: Transport + 'static>(&mut self, transport: T) {
+        self.transport = Box::new(transport);
+    }
+
+    /// Set the transport sending the http requests of the `_async` methods of the client
+    ///
+    /// Defaults to `ReqwestAsyncTransport` with the `reqwest-async` feature. See the
+    /// `transport` module.
+    pub fn set_async_transport<T: AsyncTransport + 'static>(&mut self, transport: T) {
+        self.async_transport = Box::new(transport);
+    }
+
+    /// Set how many times a request is retried after a connection error, a timeout or a
+    /// server error
+    ///
+    /// Defaults to 0. Retries wait 200ms, then twice as long for each new attempt.
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    /// Set how many requests batch methods send at the same time
+    ///
+    /// Defaults to 4.
+    pub fn set_parallelism(&mut self, parallelism: usize) {
+        self.parallelism = parallelism.max(1);
+    }
+
+    /// Take the issues recorded by lenient parsing since the last call
+    pub fn take_lenient_issues(&self) -> Vec<LenientIssue> {
+        std::mem::take(&mut *self.lenient_issues.lock().unwrap())
+    }
+
     /// Debug with println! a client
     pub fn debug(&self) {
         println!("{:#?}", self);
     }
+    /// Search cities by name
+    ///
+    /// Parameters:
+    /// * text: the searched name, e.g. "paris" or "paris, fr"
+    ///
+    /// Returns a Result with either a Vec of Location, most relevant first, or the generated error
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let mut client = accuweather::Accuweather::new(api_key, None, None);
+    ///  if let Ok(locations) = client.search_locations("paris") {
+    ///      client.set_location(locations[0].key.parse().ok());
+    ///  }
+    /// ```
+    pub fn search_locations(&self, text: &str) -> Result<Vec<Location>> {
+        self.get(self.search_locations_query(text))
+    }
+
+    /// Same as `search_locations`, also returning the json answer before typing
+    pub fn search_locations_raw(&self, text: &str) -> Result<(Value, Vec<Location>)> {
+        self.get_raw(self.search_locations_query(text))
+    }
+
+    /// Same as `search_locations`, also returning the answer status, headers and latency
+    pub fn search_locations_with_meta(&self, text: &str) -> Result<ApiResponse<Vec<Location>>> {
+        self.get_with_meta(self.search_locations_query(text))
+    }
+
     /// Get Hourly forecasts for a given period
     ///
     /// Parameters:
-    /// * period: A valid accuweather forecasts period in hours as integrer. Can be 1, 12, 24, 72, 120.
+    /// * period: A forecasts period allowed by the client subscription tier.
     ///
     /// Returns a Result with either a Vec of HourlyForecast or the generated error
     /// # Example
     /// ```
+    ///  use accuweather::periods::HourlyPeriod;
+    ///  use std::convert::TryFrom;
     ///  let api_key = "abcdefg".to_string();
     ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
-    ///  client.get_hourly_forecasts(12);
-    ///  let forecast_errors = client.get_hourly_forecasts(5);
+    ///  client.get_hourly_forecasts(HourlyPeriod::Hours12);
+    ///  assert!(HourlyPeriod::try_from(5).is_err());
+    ///  let forecast_errors = client.get_hourly_forecasts(HourlyPeriod::Hours240);
     ///  assert!(forecast_errors.is_err());
     /// ```
+    pub fn get_hourly_forecasts(&self, period: HourlyPeriod) -> Result<Vec<HourlyForecast>> {
+        self.get(self.hourly_forecasts_query(self.location()?, period)?)
+    }
 
-    pub fn get_hourly_forecasts(&self, period: i8) -> Result<Vec<HourlyForecast>> {
-        let period = match period {
-            1 | 12 | 24 | 72 | 120 => period,
-            _ => return Err(AccuweatherInvalidParameterError.into()),
-        };
-        let url = format!(
-            "{}/forecasts/v1/hourly/{}hour/{:?}",
-            self.base_url,
-            period,
-            self.location.unwrap()
-        );
-        let url = Url::parse_with_params(
-            &url,
-            &[
-                ("apikey", self.api_key.clone()),
-                ("details", "true".to_string()),
-                ("metric", "true".to_string()),
-                ("language", self.language.clone()),
-            ],
-        )?;
-        match self.client.get(url).send()?.error_for_status()?.json() {
-            Ok(x) => Ok(x),
-            Err(x) => Err(x.into()),
-        }
+    /// Same as `get_hourly_forecasts`, also returning the json answer before typing
+    pub fn get_hourly_forecasts_raw(
+        &self,
+        period: HourlyPeriod,
+    ) -> Result<(Value, Vec<HourlyForecast>)> {
+        self.get_raw(self.hourly_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Same as `get_hourly_forecasts`, also returning the answer status, headers and latency
+    pub fn get_hourly_forecasts_with_meta(
+        &self,
+        period: HourlyPeriod,
+    ) -> Result<ApiResponse<Vec<HourlyForecast>>> {
+        self.get_with_meta(self.hourly_forecasts_query(self.location()?, period)?)
     }
 
     /// Get Daily forecasts for a given period
     ///
     /// Parameters:
-    /// * period: A valid accuweather forecasts period in hours as integrer. Can be 1, 5, 10, 15.
+    /// * period: A forecasts period allowed by the client subscription tier.
     ///
     /// Returns a Result with either a DailyForecastAnswer or the generated error
     /// # Example
     /// ```
+    ///  use accuweather::periods::DailyPeriod;
     ///  let api_key = "abcdefg".to_string();
     ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
-    ///  client.get_daily_forecasts(5);
-    ///  let forecast_errors = client.get_daily_forecasts(6);
+    ///  client.get_daily_forecasts(DailyPeriod::Days5);
+    ///  let forecast_errors = client.get_daily_forecasts(DailyPeriod::Days45);
     ///  assert!(forecast_errors.is_err());
     /// ```
+    pub fn get_daily_forecasts(&self, period: DailyPeriod) -> Result<DailyForecastsAnswer> {
+        self.get(self.daily_forecasts_query(self.location()?, period)?)
+    }
 
-    pub fn get_daily_forecasts(&self, period: i8) -> Result<DailyForecastsAnswer> {
-        let period = match period {
-            1 | 5 | 10 | 15 => period,
-            _ => return Err(AccuweatherInvalidParameterError.into()),
-        };
-        let url = format!(
-            "{}/forecasts/v1/daily/{}day/{:?}",
-            self.base_url,
-            period,
-            self.location.unwrap()
-        );
-        let url = Url::parse_with_params(
-            &url,
-            &[
-                ("apikey", self.api_key.clone()),
-                ("details", "true".to_string()),
-                ("metric", "true".to_string()),
-                ("language", self.language.clone()),
-            ],
-        )?;
-        match self.client.get(url).send()?.error_for_status()?.json() {
-            Ok(x) => Ok(x),
-            Err(x) => Err(x.into()),
-        }
+    /// Same as `get_daily_forecasts`, also returning the json answer before typing
+    pub fn get_daily_forecasts_raw(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<(Value, DailyForecastsAnswer)> {
+        self.get_raw(self.daily_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Same as `get_daily_forecasts`, also returning the answer status, headers and latency
+    pub fn get_daily_forecasts_with_meta(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<ApiResponse<DailyForecastsAnswer>> {
+        self.get_with_meta(self.daily_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Get quarter-day forecasts (morning, afternoon, evening and overnight) for a given period
+    ///
+    /// Parameters:
+    /// * period: A forecasts period allowed by the client subscription tier. Can be up to 15 days.
+    ///
+    /// Returns a Result with either a Vec of QuarterForecast, four per day, or the generated error
+    /// # Example
+    /// ```
+    ///  use accuweather::periods::DailyPeriod;
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_quarter_forecasts(DailyPeriod::Days5);
+    ///  let forecast_errors = client.get_quarter_forecasts(DailyPeriod::Days25);
+    ///  assert!(forecast_errors.is_err());
+    /// ```
+    pub fn get_quarter_forecasts(&self, period: DailyPeriod) -> Result<Vec<QuarterForecast>> {
+        self.get(self.quarter_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Same as `get_quarter_forecasts`, also returning the json answer before typing
+    pub fn get_quarter_forecasts_raw(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<(Value, Vec<QuarterForecast>)> {
+        self.get_raw(self.quarter_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Same as `get_quarter_forecasts`, also returning the answer status, headers and latency
+    pub fn get_quarter_forecasts_with_meta(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<ApiResponse<Vec<QuarterForecast>>> {
+        self.get_with_meta(self.quarter_forecasts_query(self.location()?, period)?)
     }
 
     /// Get current conditions for location
@@ -200,162 +628,2948 @@ impl Accuweather {
     ///  let client = accuweather::Accuweather::new(api_key, Some(12345), Some("fr-fr".to_string()));
     ///  client.get_current_conditions();
     /// ```
-
     pub fn get_current_conditions(&self) -> Result<Vec<CurrentCondition>> {
-        let url = format!(
-            "{}/currentconditions/v1/{:?}",
-            self.base_url,
-            self.location.unwrap()
-        );
-        let url = Url::parse_with_params(
-            &url,
-            &[
-                ("apikey", self.api_key.clone()),
-                ("details", "true".to_string()),
-                ("language", self.language.clone()),
-            ],
-        )?;
-        match self.client.get(url).send()?.error_for_status()?.json() {
-            Ok(x) => Ok(x),
-            Err(x) => Err(x.into()),
-        }
+        self.get(self.current_conditions_query(self.location()?)?)
     }
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use mockito::{mock, Matcher};
-    use std::fs;
 
-    fn set_mocks() -> Vec<mockito::Mock> {
-        let mut res = Vec::new();
-        let daily5_json = fs::read_to_string("assets/daily5.json").unwrap();
-        let _mdnokforbidden = mock("GET", "/forecasts/v1/daily/5day/12345")
-            .with_status(403)
-            .create();
-        res.push(_mdnokforbidden);
-        let _mdok = mock("GET", "/forecasts/v1/daily/5day/12345")
-            .with_status(200)
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
-                Matcher::UrlEncoded("details".into(), "true".into()),
-                Matcher::UrlEncoded("metric".into(), "true".into()),
-                Matcher::UrlEncoded("language".into(), "en-us".into()),
-            ]))
-            .with_body(&daily5_json)
-            .create();
-        res.push(_mdok);
-        let hourly12_json = fs::read_to_string("assets/hourly12.json").unwrap();
-        let _mhnokforbidden = mock("GET", "/forecasts/v1/hourly/12hour/12345")
-            .with_status(403)
-            .create();
-        res.push(_mhnokforbidden);
-        let _mhok = mock("GET", "/forecasts/v1/hourly/12hour/12345")
-            .with_status(200)
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
-                Matcher::UrlEncoded("details".into(), "true".into()),
-                Matcher::UrlEncoded("metric".into(), "true".into()),
-                Matcher::UrlEncoded("language".into(), "en-us".into()),
-            ]))
-            .with_body(&hourly12_json)
-            .create();
-        res.push(_mhok);
+    /// Same as `get_current_conditions`, also returning the json answer before typing
+    pub fn get_current_conditions_raw(&self) -> Result<(Value, Vec<CurrentCondition>)> {
+        self.get_raw(self.current_conditions_query(self.location()?)?)
+    }
 
-        let conditions_json = fs::read_to_string("assets/conditions.json").unwrap();
-        let _mcnokforbidden = mock("GET", "/currentconditions/v1/12345")
-            .with_status(403)
-            .create();
-        res.push(_mcnokforbidden);
-        let _mcok = mock("GET", "/currentconditions/v1/12345")
-            .with_status(200)
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
-                Matcher::UrlEncoded("details".into(), "true".into()),
-                Matcher::UrlEncoded("language".into(), "en-us".into()),
-            ]))
-            .with_body(&conditions_json)
-            .create();
-        res.push(_mcok);
+    /// Same as `get_current_conditions`, also returning the answer status, headers and latency
+    pub fn get_current_conditions_with_meta(&self) -> Result<ApiResponse<Vec<CurrentCondition>>> {
+        self.get_with_meta(self.current_conditions_query(self.location()?)?)
+    }
 
-        res
+    /// Get current air quality for location
+    ///
+    /// Returns a Result with either an AirQuality or the generated error
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_air_quality_current_conditions();
+    /// ```
+    pub fn get_air_quality_current_conditions(&self) -> Result<AirQuality> {
+        self.get(self.air_quality_current_conditions_query(self.location()?)?)
     }
 
-    #[test]
-    fn test_daily_forecast_ok() {
-        let _mocks = set_mocks();
-        let api_key = "abcdefg".to_string();
-        let client = Accuweather::new(api_key, Some(12345), None);
-        let res_forecasts = client.get_daily_forecasts(5);
-        let forecasts = res_forecasts.unwrap();
-        assert_eq!(forecasts.daily_forecasts[0].temperature.minimum.value, 5.4);
+    /// Same as `get_air_quality_current_conditions`, also returning the json answer before typing
+    pub fn get_air_quality_current_conditions_raw(&self) -> Result<(Value, AirQuality)> {
+        self.get_raw(self.air_quality_current_conditions_query(self.location()?)?)
     }
-    #[test]
-    fn test_daily_forecast_nok_forbidden() {
-        let _mocks = set_mocks();
-        let api_key = "bad_key".to_string();
-        let client = Accuweather::new(api_key, Some(12345), None);
-        let res_forecasts = client.get_daily_forecasts(5);
-        assert!(res_forecasts.is_err());
+
+    /// Same as `get_air_quality_current_conditions`, also returning the answer status, headers and latency
+    pub fn get_air_quality_current_conditions_with_meta(&self) -> Result<ApiResponse<AirQuality>> {
+        self.get_with_meta(self.air_quality_current_conditions_query(self.location()?)?)
     }
-    #[test]
-    fn test_daily_forecast_nok_badlocation() {
-        let _mocks = set_mocks();
-        let api_key = "bad_key".to_string();
-        let client = Accuweather::new(api_key, Some(123456), None);
-        let res_forecasts = client.get_daily_forecasts(5);
-        assert!(res_forecasts.is_err());
+
+    /// Get hourly air quality forecasts for a given period
+    ///
+    /// Parameters:
+    /// * period: An air quality forecasts period, from 1 to 96 hours.
+    ///
+    /// Returns a Result with either a Vec of AirQuality or the generated error
+    /// # Example
+    /// ```
+    ///  use accuweather::periods::AirQualityHourlyPeriod;
+    ///  use std::convert::TryFrom;
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_air_quality_hourly_forecasts(AirQualityHourlyPeriod::Hours12);
+    ///  assert!(AirQualityHourlyPeriod::try_from(5).is_err());
+    /// ```
+    pub fn get_air_quality_hourly_forecasts(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<Vec<AirQuality>> {
+        self.get(self.air_quality_hourly_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Same as `get_air_quality_hourly_forecasts`, also returning the json answer before typing
+    pub fn get_air_quality_hourly_forecasts_raw(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<(Value, Vec<AirQuality>)> {
+        self.get_raw(self.air_quality_hourly_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Same as `get_air_quality_hourly_forecasts`, also returning the answer status, headers and latency
+    pub fn get_air_quality_hourly_forecasts_with_meta(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<ApiResponse<Vec<AirQuality>>> {
+        self.get_with_meta(self.air_quality_hourly_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Get daily air quality forecasts for a given period
+    ///
+    /// Parameters:
+    /// * period: An air quality forecasts period, 1 or 4 days.
+    ///
+    /// Returns a Result with either a Vec of AirQuality or the generated error
+    /// # Example
+    /// ```
+    ///  use accuweather::periods::AirQualityDailyPeriod;
+    ///  use std::convert::TryFrom;
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_air_quality_daily_forecasts(AirQualityDailyPeriod::Days4);
+    ///  assert!(AirQualityDailyPeriod::try_from(5).is_err());
+    /// ```
+    pub fn get_air_quality_daily_forecasts(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<Vec<AirQuality>> {
+        self.get(self.air_quality_daily_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Same as `get_air_quality_daily_forecasts`, also returning the json answer before typing
+    pub fn get_air_quality_daily_forecasts_raw(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<(Value, Vec<AirQuality>)> {
+        self.get_raw(self.air_quality_daily_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Same as `get_air_quality_daily_forecasts`, also returning the answer status, headers and latency
+    pub fn get_air_quality_daily_forecasts_with_meta(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<ApiResponse<Vec<AirQuality>>> {
+        self.get_with_meta(self.air_quality_daily_forecasts_query(self.location()?, period)?)
+    }
+
+    /// Get climatological actuals observed at location for a given day
+    ///
+    /// Parameters:
+    /// * year, month, day: the date to get actuals for. Month must be within 1..=12 and day within 1..=31.
+    ///
+    /// Returns a Result with either a ClimoActualsAnswer or the generated error
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_climo_actuals(2019, 5, 28);
+    ///  let climo_errors = client.get_climo_actuals(2019, 13, 28);
+    ///  assert!(climo_errors.is_err());
+    /// ```
+    pub fn get_climo_actuals(&self, year: i32, month: u8, day: u8) -> Result<ClimoActualsAnswer> {
+        self.get(self.climo_day_query(self.location()?, "actuals", year, month, day)?)
+    }
+
+    /// Same as `get_climo_actuals`, also returning the json answer before typing
+    pub fn get_climo_actuals_raw(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<(Value, ClimoActualsAnswer)> {
+        self.get_raw(self.climo_day_query(self.location()?, "actuals", year, month, day)?)
+    }
+
+    /// Same as `get_climo_actuals`, also returning the answer status, headers and latency
+    pub fn get_climo_actuals_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoActualsAnswer>> {
+        self.get_with_meta(self.climo_day_query(self.location()?, "actuals", year, month, day)?)
+    }
+
+    /// Get climatological normals at location for a given day
+    ///
+    /// Parameters:
+    /// * year, month, day: the date to get normals for. Month must be within 1..=12 and day within 1..=31.
+    ///
+    /// Returns a Result with either a ClimoNormalsAnswer or the generated error
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_climo_normals(2019, 5, 28);
+    /// ```
+    pub fn get_climo_normals(&self, year: i32, month: u8, day: u8) -> Result<ClimoNormalsAnswer> {
+        self.get(self.climo_day_query(self.location()?, "normals", year, month, day)?)
+    }
+
+    /// Same as `get_climo_normals`, also returning the json answer before typing
+    pub fn get_climo_normals_raw(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<(Value, ClimoNormalsAnswer)> {
+        self.get_raw(self.climo_day_query(self.location()?, "normals", year, month, day)?)
+    }
+
+    /// Same as `get_climo_normals`, also returning the answer status, headers and latency
+    pub fn get_climo_normals_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoNormalsAnswer>> {
+        self.get_with_meta(self.climo_day_query(self.location()?, "normals", year, month, day)?)
+    }
+
+    /// Get climatological records at location for a given day
+    ///
+    /// Parameters:
+    /// * year, month, day: the date to get records for. Month must be within 1..=12 and day within 1..=31.
+    ///
+    /// Returns a Result with either a ClimoRecordsAnswer or the generated error
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_climo_records(2019, 5, 28);
+    /// ```
+    pub fn get_climo_records(&self, year: i32, month: u8, day: u8) -> Result<ClimoRecordsAnswer> {
+        self.get(self.climo_day_query(self.location()?, "records", year, month, day)?)
+    }
+
+    /// Same as `get_climo_records`, also returning the json answer before typing
+    pub fn get_climo_records_raw(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<(Value, ClimoRecordsAnswer)> {
+        self.get_raw(self.climo_day_query(self.location()?, "records", year, month, day)?)
+    }
+
+    /// Same as `get_climo_records`, also returning the answer status, headers and latency
+    pub fn get_climo_records_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoRecordsAnswer>> {
+        self.get_with_meta(self.climo_day_query(self.location()?, "records", year, month, day)?)
+    }
+
+    /// Get climatological actuals, normals and records at location for every day of a month
+    ///
+    /// Parameters:
+    /// * year, month: the month to get the summary for. Month must be within 1..=12.
+    ///
+    /// Returns a Result with either a ClimoSummaryAnswer or the generated error
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_climo_month_summary(2019, 5);
+    ///  let climo_errors = client.get_climo_month_summary(2019, 0);
+    ///  assert!(climo_errors.is_err());
+    /// ```
+    pub fn get_climo_month_summary(&self, year: i32, month: u8) -> Result<ClimoSummaryAnswer> {
+        self.get(self.climo_month_summary_query(self.location()?, year, month)?)
+    }
+
+    /// Same as `get_climo_month_summary`, also returning the json answer before typing
+    pub fn get_climo_month_summary_raw(
+        &self,
+        year: i32,
+        month: u8,
+    ) -> Result<(Value, ClimoSummaryAnswer)> {
+        self.get_raw(self.climo_month_summary_query(self.location()?, year, month)?)
+    }
+
+    /// Same as `get_climo_month_summary`, also returning the answer status, headers and latency
+    pub fn get_climo_month_summary_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+    ) -> Result<ApiResponse<ClimoSummaryAnswer>> {
+        self.get_with_meta(self.climo_month_summary_query(self.location()?, year, month)?)
+    }
+
+    /// Get radar and satellite imagery for location
+    ///
+    /// Parameters:
+    /// * resolution: A valid accuweather imagery resolution. Can be "480x480", "640x480", "1024x1024".
+    ///
+    /// Returns a Result with either an ImageryAnswer or the generated error
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, Some(12345), None);
+    ///  client.get_imagery("480x480");
+    ///  let imagery_errors = client.get_imagery("480x481");
+    ///  assert!(imagery_errors.is_err());
+    /// ```
+    pub fn get_imagery(&self, resolution: &str) -> Result<ImageryAnswer> {
+        self.get(self.imagery_query(self.location()?, resolution)?)
+    }
+
+    /// Same as `get_imagery`, also returning the json answer before typing
+    pub fn get_imagery_raw(&self, resolution: &str) -> Result<(Value, ImageryAnswer)> {
+        self.get_raw(self.imagery_query(self.location()?, resolution)?)
+    }
+
+    /// Same as `get_imagery`, also returning the answer status, headers and latency
+    pub fn get_imagery_with_meta(&self, resolution: &str) -> Result<ApiResponse<ImageryAnswer>> {
+        self.get_with_meta(self.imagery_query(self.location()?, resolution)?)
+    }
+
+    /// Get radar frames for location, oldest first
+    ///
+    /// Parameters:
+    /// * resolution: A valid accuweather imagery resolution. Can be "480x480", "640x480", "1024x1024".
+    ///
+    /// Returns a Result with either a Vec of ImageryFrame or the generated error
+    pub fn get_radar_frames(&self, resolution: &str) -> Result<Vec<ImageryFrame>> {
+        Ok(self.get_imagery(resolution)?.radar_frames())
+    }
+
+    /// Get satellite frames for location, oldest first
+    ///
+    /// Parameters:
+    /// * resolution: A valid accuweather imagery resolution. Can be "480x480", "640x480", "1024x1024".
+    ///
+    /// Returns a Result with either a Vec of ImageryFrame or the generated error
+    pub fn get_satellite_frames(&self, resolution: &str) -> Result<Vec<ImageryFrame>> {
+        Ok(self.get_imagery(resolution)?.satellite_frames())
+    }
+
+    /// Get current conditions for several locations, fetched concurrently
+    ///
+    /// Parameters:
+    /// * locations: the location keys
+    ///
+    /// At most `parallelism` requests are sent at the same time. Once every api key is out of
+    /// quota, the locations left fail with an AccuweatherQuotaError without being requested.
+    ///
+    /// Returns a map of each location to either its Vec of CurrentCondition or its error
+    /// # Example
+    /// ```
+    ///  let api_key = "abcdefg".to_string();
+    ///  let client = accuweather::Accuweather::new(api_key, None, None);
+    ///  for (location, conditions) in client.get_current_conditions_batch(&[12345, 623]) {
+    ///      match conditions {
+    ///          Ok(conditions) => println!("{}: {}", location, conditions[0].weather_text),
+    ///          Err(error) => println!("{}: {}", location, error),
+    ///      }
+    ///  }
+    /// ```
+    pub fn get_current_conditions_batch(
+        &self,
+        locations: &[i32],
+    ) -> HashMap<i32, SendResult<Vec<CurrentCondition>>> {
+        self.batch(locations, |location| {
+            self.current_conditions_query(location)
+        })
+    }
+
+    /// Get Hourly forecasts of a given period for several locations, fetched concurrently
+    ///
+    /// Behaves as `get_current_conditions_batch`.
+    pub fn get_hourly_forecasts_batch(
+        &self,
+        locations: &[i32],
+        period: HourlyPeriod,
+    ) -> HashMap<i32, SendResult<Vec<HourlyForecast>>> {
+        self.batch(locations, |location| {
+            self.hourly_forecasts_query(location, period)
+        })
+    }
+
+    /// Get Daily forecasts of a given period for several locations, fetched concurrently
+    ///
+    /// Behaves as `get_current_conditions_batch`.
+    pub fn get_daily_forecasts_batch(
+        &self,
+        locations: &[i32],
+        period: DailyPeriod,
+    ) -> HashMap<i32, SendResult<DailyForecastsAnswer>> {
+        self.batch(locations, |location| {
+            self.daily_forecasts_query(location, period)
+        })
+    }
+
+    /// Download the image of a frame with the client
+    ///
+    /// The image is recorded to, or replayed from, the cassette of the client.
+    /// Returns a Result with either the raw image bytes or the generated error
+    pub fn download_imagery_frame(&self, frame: &ImageryFrame) -> Result<Vec<u8>> {
+        let (url, request) = self.frame_request(frame).map_err(unsend)?;
+        let response = match self.replaying() {
+            Some(cassette) => cassette.replay_answer(&url)?,
+            None => self
+                .transport
+                .get(&request)
+                .map_err(|error| self.transport_error(error))?,
+        };
+        self.frame_image(&url, &request, response).map_err(unsend)
+    }
+
+    /// Get the answer of an endpoint for a location, through the async transport
+    ///
+    /// The requests are sent by the transport set with `set_async_transport`,
+    /// `ReqwestAsyncTransport` by default with the `reqwest-async` feature.
+    ///
+    /// Parameters:
+    /// * location: the location key
+    /// * endpoint: the requested endpoint
+    ///
+    /// Keys, cassette and parsing behave as with the blocking methods. Retries wait on the
+    /// timer of the transport, see `AsyncTransport::sleep`; requests are not retried when it
+    /// has none.
+    ///
+    /// Returns a Result with either the Answer or the generated error
+    pub async fn get_endpoint_async(
+        &self,
+        location: i32,
+        endpoint: Endpoint,
+    ) -> SendResult<Answer> {
+        let query = schedule::endpoint_query(self, location, endpoint)?;
+        let response = self.fetch_async(&query).await?;
+        schedule::parse_answer(self, endpoint, &response.body)
+    }
+
+    /// Same as `search_locations`, through the async transport as `get_endpoint_async`
+    pub async fn search_locations_async(&self, text: &str) -> SendResult<Vec<Location>> {
+        self.get_async(self.search_locations_query(text)).await
+    }
+
+    /// Same as `get_hourly_forecasts`, through the async transport as `get_endpoint_async`
+    pub async fn get_hourly_forecasts_async(
+        &self,
+        period: HourlyPeriod,
+    ) -> SendResult<Vec<HourlyForecast>> {
+        let query = self.hourly_forecasts_query(self.location()?, period)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_daily_forecasts`, through the async transport as `get_endpoint_async`
+    pub async fn get_daily_forecasts_async(
+        &self,
+        period: DailyPeriod,
+    ) -> SendResult<DailyForecastsAnswer> {
+        let query = self.daily_forecasts_query(self.location()?, period)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_quarter_forecasts`, through the async transport as `get_endpoint_async`
+    pub async fn get_quarter_forecasts_async(
+        &self,
+        period: DailyPeriod,
+    ) -> SendResult<Vec<QuarterForecast>> {
+        let query = self.quarter_forecasts_query(self.location()?, period)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_current_conditions`, through the async transport as `get_endpoint_async`
+    pub async fn get_current_conditions_async(&self) -> SendResult<Vec<CurrentCondition>> {
+        let query = self.current_conditions_query(self.location()?)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_air_quality_current_conditions`, through the async transport as
+    /// `get_endpoint_async`
+    pub async fn get_air_quality_current_conditions_async(&self) -> SendResult<AirQuality> {
+        let query = self.air_quality_current_conditions_query(self.location()?)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_air_quality_hourly_forecasts`, through the async transport as
+    /// `get_endpoint_async`
+    pub async fn get_air_quality_hourly_forecasts_async(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> SendResult<Vec<AirQuality>> {
+        let query = self.air_quality_hourly_forecasts_query(self.location()?, period)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_air_quality_daily_forecasts`, through the async transport as
+    /// `get_endpoint_async`
+    pub async fn get_air_quality_daily_forecasts_async(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> SendResult<Vec<AirQuality>> {
+        let query = self.air_quality_daily_forecasts_query(self.location()?, period)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_climo_actuals`, through the async transport as `get_endpoint_async`
+    pub async fn get_climo_actuals_async(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> SendResult<ClimoActualsAnswer> {
+        let query = self.climo_day_query(self.location()?, "actuals", year, month, day)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_climo_normals`, through the async transport as `get_endpoint_async`
+    pub async fn get_climo_normals_async(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> SendResult<ClimoNormalsAnswer> {
+        let query = self.climo_day_query(self.location()?, "normals", year, month, day)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_climo_records`, through the async transport as `get_endpoint_async`
+    pub async fn get_climo_records_async(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> SendResult<ClimoRecordsAnswer> {
+        let query = self.climo_day_query(self.location()?, "records", year, month, day)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_climo_month_summary`, through the async transport as `get_endpoint_async`
+    pub async fn get_climo_month_summary_async(
+        &self,
+        year: i32,
+        month: u8,
+    ) -> SendResult<ClimoSummaryAnswer> {
+        let query = self.climo_month_summary_query(self.location()?, year, month)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `get_imagery`, through the async transport as `get_endpoint_async`
+    pub async fn get_imagery_async(&self, resolution: &str) -> SendResult<ImageryAnswer> {
+        let query = self.imagery_query(self.location()?, resolution)?;
+        self.get_async(query).await
+    }
+
+    /// Same as `download_imagery_frame`, through the async transport
+    pub async fn download_imagery_frame_async(&self, frame: &ImageryFrame) -> SendResult<Vec<u8>> {
+        let (url, request) = self.frame_request(frame)?;
+        let response = match self.replaying() {
+            Some(cassette) => cassette.replay_answer(&url)?,
+            None => self
+                .async_transport
+                .get(&request)
+                .await
+                .map_err(|error| self.transport_error(error))?,
+        };
+        self.frame_image(&url, &request, response)
+    }
+
+    /// Query the api through the async transport and parse the json answer.
+    async fn get_async<T: DeserializeOwned>(&self, query: Query) -> SendResult<T> {
+        let value = self.fetch_async(&query).await?.body;
+        self.parse(&value)
+    }
+
+    /// Url and request of the image of a frame.
+    fn frame_request(&self, frame: &ImageryFrame) -> SendResult<(Url, HttpRequest)> {
+        let url = Url::parse(&frame.url)?;
+        let request = HttpRequest {
+            url: frame.url.clone(),
+            headers: Vec::new(),
+            timeout: self.timeout,
+        };
+        Ok((url, request))
+    }
+
+    /// Image of a frame from its answer, recording it to the cassette.
+    fn frame_image(
+        &self,
+        url: &Url,
+        request: &HttpRequest,
+        response: HttpResponse,
+    ) -> SendResult<Vec<u8>> {
+        if let Some(cassette) = &self.cassette {
+            cassette.record_binary_answer(url, &response);
+        }
+        if response.status >= 400 {
+            return Err(self.status_error(request, response.status).into());
+        }
+        Ok(response.body)
+    }
+
+    /// Query the api and parse the json answer.
+    fn get<T: DeserializeOwned>(&self, query: Query) -> Result<T> {
+        self.get_send(&query).map_err(unsend)
+    }
+
+    /// Query the api and parse the json answer, keeping it along the typed answer.
+    fn get_raw<T: DeserializeOwned>(&self, query: Query) -> Result<(Value, T)> {
+        let value = self.fetch(&query).map_err(unsend)?.body;
+        let parsed = self.parse(&value).map_err(unsend)?;
+        Ok((value, parsed))
+    }
+
+    /// Query the api and parse the json answer, keeping the answer metadata.
+    fn get_with_meta<T: DeserializeOwned>(&self, query: Query) -> Result<ApiResponse<T>> {
+        let response = self.fetch(&query).map_err(unsend)?;
+        let parsed = self.parse(&response.body).map_err(unsend)?;
+        Ok(response.map(|_| parsed))
+    }
+
+    /// Query the api for each location, with at most `parallelism` requests at the same time.
+    fn batch<T, F>(&self, locations: &[i32], query: F) -> HashMap<i32, SendResult<T>>
+    where
+        T: DeserializeOwned + Send,
+        F: Fn(i32) -> ParameterResult<Query> + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(HashMap::new());
+        let workers = self.parallelism.clamp(1, locations.len().max(1));
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    while let Some(&location) = locations.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let result = if self.keys.quota_exhausted() {
+                            Err(AccuweatherQuotaError.into())
+                        } else {
+                            query(location)
+                                .map_err(Into::into)
+                                .and_then(|query| self.get_send(&query))
+                        };
+                        results.lock().unwrap().insert(location, result);
+                    }
+                });
+            }
+        });
+        results.into_inner().unwrap()
+    }
+
+    /// Query the api and parse the json answer, with an error that can be sent between threads.
+    fn get_send<T: DeserializeOwned>(&self, query: &Query) -> SendResult<T> {
+        let value = self.fetch(query)?.body;
+        self.parse(&value)
+    }
+
+    /// Url of a request, with its query parameters and language but without credentials.
+    fn request_url(&self, query: &Query) -> SendResult<Url> {
+        let url = format!("{}{}", self.base_url, query.path);
+        let mut params: Vec<(&str, &str)> = query
+            .params
+            .iter()
+            .map(|(key, value)| match *key {
+                "metric" => (*key, self.units.metric_parameter()),
+                _ => (*key, value.as_str()),
+            })
+            .collect();
+        params.push(("language", self.language.as_str()));
+        Ok(Url::parse_with_params(&url, &params)?)
+    }
+
+    /// The cassette of the client, if it replays answers.
+    fn replaying(&self) -> Option<&Cassette> {
+        self.cassette
+            .as_ref()
+            .filter(|cassette| cassette.is_replaying())
+    }
+
+    /// Query the api, sending the api key as set by the auth mode.
+    ///
+    /// A replaying cassette answers each attempt in place of the transport.
+    fn fetch(&self, query: &Query) -> SendResult<ApiResponse<Value>> {
+        let url = self.request_url(query)?;
+        let start = Instant::now();
+        let mut attempt = 0;
+        let mut tried = Vec::new();
+        let mut failure = None;
+        while let Some((index, key)) = self.keys.select(&tried) {
+            let request = self.http_request(&url, &key);
+            let result = match self.replaying() {
+                Some(cassette) => Ok(cassette.replay_answer(&url)?),
+                None => self.transport.get(&request),
+            };
+            match self.outcome(&url, &request, index, result, start) {
+                Outcome::Done(result) => return result,
+                Outcome::Failover(error) => {
+                    tried.push(index);
+                    failure = Some(error);
+                }
+                Outcome::Retry(_) if attempt < self.retries => {
+                    if self.replaying().is_none() {
+                        thread::sleep(retry_delay(attempt));
+                    }
+                    attempt += 1;
+                }
+                Outcome::Retry(error) => return Err(error.into()),
+            }
+        }
+        Err(failure.map_or_else(|| "no usable api key".into(), Into::into))
+    }
+
+    /// Query the api as `fetch` does, through the async transport waiting with its timer.
+    async fn fetch_async(&self, query: &Query) -> SendResult<ApiResponse<Value>> {
+        let url = self.request_url(query)?;
+        let start = Instant::now();
+        let mut attempt = 0;
+        let mut tried = Vec::new();
+        let mut failure = None;
+        while let Some((index, key)) = self.keys.select(&tried) {
+            let request = self.http_request(&url, &key);
+            let result = match self.replaying() {
+                Some(cassette) => Ok(cassette.replay_answer(&url)?),
+                None => self.async_transport.get(&request).await,
+            };
+            match self.outcome(&url, &request, index, result, start) {
+                Outcome::Done(result) => return result,
+                Outcome::Failover(error) => {
+                    tried.push(index);
+                    failure = Some(error);
+                }
+                Outcome::Retry(error) if attempt < self.retries => {
+                    if self.replaying().is_none() {
+                        match self.async_transport.sleep(retry_delay(attempt)) {
+                            Some(sleep) => sleep.await,
+                            None => return Err(error.into()),
+                        }
+                    }
+                    attempt += 1;
+                }
+                Outcome::Retry(error) => return Err(error.into()),
+            }
+        }
+        Err(failure.map_or_else(|| "no usable api key".into(), Into::into))
+    }
+
+    /// Handle the result of a request sent with the key at `index`, recording the answer to
+    /// the cassette and the key state.
+    fn outcome(
+        &self,
+        url: &Url,
+        request: &HttpRequest,
+        index: usize,
+        result: std::result::Result<HttpResponse, TransportError>,
+        start: Instant,
+    ) -> Outcome {
+        let response = match result {
+            Ok(response) => response,
+            Err(error) if error.is_transient() => {
+                return Outcome::Retry(self.transport_error(error))
+            }
+            Err(error) => return Outcome::Done(Err(self.transport_error(error).into())),
+        };
+        let text = response.text();
+        if let Some(cassette) = &self.cassette {
+            let text = self.keys.redact(&text);
+            cassette.record_answer(url, response.status, &response.headers, &text);
+        }
+        if let Some(state) = key_rejection(&response) {
+            self.keys.record_failure(index, state);
+            return Outcome::Failover(self.status_error(request, response.status));
+        }
+        match response.status {
+            500..=599 => return Outcome::Retry(self.status_error(request, response.status)),
+            400..=499 => {
+                return Outcome::Done(Err(self.status_error(request, response.status).into()))
+            }
+            _ => {}
+        }
+        let body = match serde_json::from_str(&text) {
+            Ok(body) => body,
+            Err(error) => return Outcome::Done(Err(body_error(error).into())),
+        };
+        let response = ApiResponse::new(
+            url,
+            &response.headers,
+            response.status,
+            start.elapsed(),
+            body,
+        );
+        self.keys
+            .record_success(index, response.rate_limit_remaining);
+        Outcome::Done(Ok(response))
+    }
+
+    /// Build a request to `url`, authenticated as set by the auth mode.
+    fn http_request(&self, url: &Url, key: &ApiKey) -> HttpRequest {
+        let mut url = url.clone();
+        let mut headers = Vec::new();
+        match self.auth_mode {
+            AuthMode::QueryParameter => {
+                url.query_pairs_mut().append_pair("apikey", key.expose());
+            }
+            AuthMode::Header => {
+                headers.push((
+                    "Authorization".to_string(),
+                    format!("Bearer {}", key.expose()),
+                ));
+            }
+        }
+        HttpRequest {
+            url: url.to_string(),
+            headers,
+            timeout: self.timeout,
+        }
+    }
+
+    /// Convert a transport error, removing the api key from its message.
+    fn transport_error(&self, error: TransportError) -> AccuweatherHttpError {
+        AccuweatherHttpError {
+            status: None,
+            message: self.keys.redact(&error.to_string()),
+        }
+    }
+
+    /// Error of a request answered with an error status, without the api key.
+    fn status_error(&self, request: &HttpRequest, status: u16) -> AccuweatherHttpError {
+        AccuweatherHttpError {
+            status: Some(status),
+            message: self
+                .keys
+                .redact(&format!("{}: status {}", request.url, status)),
+        }
+    }
+
+    /// Parse an api answer, leniently if the client is set so.
+    fn parse<T: DeserializeOwned>(&self, value: &Value) -> SendResult<T> {
+        let parsed = if self.lenient {
+            lenient::parse(value.clone()).map(|(parsed, issues)| {
+                self.lenient_issues.lock().unwrap().extend(issues);
+                parsed
+            })
+        } else {
+            T::deserialize(value)
+        };
+        parsed.map_err(|error| {
+            AccuweatherParseError {
+                payload: value.clone(),
+                error,
+            }
+            .into()
+        })
+    }
+
+    /// Error of a period the subscription tier doesn't allow.
+    fn tier_error(
+        &self,
+        parameter: &'static str,
+        length: u16,
+        unit: &str,
+    ) -> AccuweatherInvalidParameterError {
+        AccuweatherInvalidParameterError::new(
+            parameter,
+            format!("{} {}", length, unit),
+            format!(
+                "a period of the {:?} subscription tier",
+                self.subscription_tier
+            ),
+        )
+    }
+
+    /// Location of the client, an error when it isn't set.
+    fn location(&self) -> ParameterResult<i32> {
+        self.location.ok_or_else(|| {
+            AccuweatherInvalidParameterError::new(
+                "location",
+                "none",
+                "a location key, see set_location",
+            )
+        })
+    }
+
+    fn search_locations_query(&self, text: &str) -> Query {
+        let path = "/locations/v1/cities/search".to_string();
+        Query::new(path, &[("q", text)])
+    }
+
+    fn hourly_forecasts_query(
+        &self,
+        location: i32,
+        period: HourlyPeriod,
+    ) -> ParameterResult<Query> {
+        if !self.subscription_tier.allows_hourly(period) {
+            return Err(self.tier_error("hourly period", period.hours(), "hours"));
+        }
+        let path = format!("/forecasts/v1/hourly/{}hour/{:?}", period.hours(), location);
+        Ok(Query::new(path, &[("details", "true"), ("metric", "true")]))
+    }
+
+    fn daily_forecasts_query(&self, location: i32, period: DailyPeriod) -> ParameterResult<Query> {
+        if !self.subscription_tier.allows_daily(period) {
+            return Err(self.tier_error("daily period", period.days(), "days"));
+        }
+        let path = format!("/forecasts/v1/daily/{}day/{:?}", period.days(), location);
+        Ok(Query::new(path, &[("details", "true"), ("metric", "true")]))
+    }
+
+    fn quarter_forecasts_query(
+        &self,
+        location: i32,
+        period: DailyPeriod,
+    ) -> ParameterResult<Query> {
+        if period > DailyPeriod::Days15 {
+            return Err(AccuweatherInvalidParameterError::new(
+                "quarter forecast period",
+                format!("{} days", period.days()),
+                "1, 5, 10 or 15 days",
+            ));
+        }
+        if !self.subscription_tier.allows_daily(period) {
+            return Err(self.tier_error("quarter forecast period", period.days(), "days"));
+        }
+        let path = format!(
+            "/forecasts/v1/daily/{}day/quarters/{:?}",
+            period.days(),
+            location
+        );
+        Ok(Query::new(path, &[("details", "true"), ("metric", "true")]))
+    }
+
+    fn current_conditions_query(&self, location: i32) -> ParameterResult<Query> {
+        let path = format!("/currentconditions/v1/{:?}", location);
+        Ok(Query::new(path, &[("details", "true")]))
+    }
+
+    fn air_quality_current_conditions_query(&self, location: i32) -> ParameterResult<Query> {
+        let path = format!("/airquality/v2/currentconditions/{:?}", location);
+        Ok(Query::new(path, &[]))
+    }
+
+    fn air_quality_hourly_forecasts_query(
+        &self,
+        location: i32,
+        period: AirQualityHourlyPeriod,
+    ) -> ParameterResult<Query> {
+        let path = format!(
+            "/airquality/v2/forecasts/hourly/{}hour/{:?}",
+            period.hours(),
+            location
+        );
+        Ok(Query::new(path, &[]))
+    }
+
+    fn air_quality_daily_forecasts_query(
+        &self,
+        location: i32,
+        period: AirQualityDailyPeriod,
+    ) -> ParameterResult<Query> {
+        let path = format!(
+            "/airquality/v2/forecasts/daily/{}day/{:?}",
+            period.days(),
+            location
+        );
+        Ok(Query::new(path, &[]))
+    }
+
+    fn climo_month_summary_query(
+        &self,
+        location: i32,
+        year: i32,
+        month: u8,
+    ) -> ParameterResult<Query> {
+        let month = match month {
+            1..=12 => month,
+            _ => {
+                return Err(AccuweatherInvalidParameterError::new(
+                    "month", month, "1 to 12",
+                ))
+            }
+        };
+        let path = format!("/climo/v1/summary/{}/{}/{:?}", year, month, location);
+        Ok(Query::new(path, &[("details", "true"), ("metric", "true")]))
+    }
+
+    fn imagery_query(&self, location: i32, resolution: &str) -> ParameterResult<Query> {
+        let resolution = match resolution {
+            "480x480" | "640x480" | "1024x1024" => resolution,
+            _ => {
+                return Err(AccuweatherInvalidParameterError::new(
+                    "imagery resolution",
+                    resolution,
+                    "480x480, 640x480 or 1024x1024",
+                ))
+            }
+        };
+        let path = format!("/imagery/v1/maps/radsat/{}/{:?}", resolution, location);
+        Ok(Query::new(path, &[]))
+    }
+
+    fn climo_day_query(
+        &self,
+        location: i32,
+        kind: &str,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> ParameterResult<Query> {
+        if !(1..=12).contains(&month) {
+            return Err(AccuweatherInvalidParameterError::new(
+                "month", month, "1 to 12",
+            ));
+        }
+        let days = days_in_month(year, month);
+        if day < 1 || day > days {
+            return Err(AccuweatherInvalidParameterError::new(
+                "day",
+                format!("{}-{:02}-{:02}", year, month, day),
+                format!("1 to {}", days),
+            ));
+        }
+        let path = format!(
+            "/climo/v1/{}/{}/{}/{}/{:?}",
+            kind, year, month, day, location
+        );
+        Ok(Query::new(path, &[("details", "true"), ("metric", "true")]))
+    }
+}
+
+/// Number of days of a month, between 1 and 12, of the gregorian calendar
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icons::*;
+    use crate::transport::TransportFuture;
+    use mockito::{mock, Matcher};
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+    use std::fs;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    /// A client of the mockito server
+    fn mock_client(
+        api_key: String,
+        location: Option<i32>,
+        language: Option<String>,
+    ) -> Accuweather {
+        let mut client = Accuweather::new(api_key, location, language);
+        client.set_base_url(mockito::server_url());
+        client
+    }
+
+    /// Transport answering every request with the same answer
+    #[derive(Debug)]
+    struct FixedTransport {
+        status: u16,
+        body: String,
+        requests: Arc<Mutex<Vec<HttpRequest>>>,
+    }
+
+    impl FixedTransport {
+        fn answer(&self, request: &HttpRequest) -> HttpResponse {
+            self.requests.lock().unwrap().push(request.clone());
+            HttpResponse {
+                status: self.status,
+                headers: vec![("ratelimit-remaining".to_string(), "42".to_string())],
+                body: self.body.clone().into_bytes(),
+            }
+        }
+    }
+
+    impl Transport for FixedTransport {
+        fn get(&self, request: &HttpRequest) -> std::result::Result<HttpResponse, TransportError> {
+            Ok(self.answer(request))
+        }
+    }
+
+    impl AsyncTransport for FixedTransport {
+        fn get<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+            Box::pin(async move { Ok(self.answer(request)) })
+        }
+    }
+
+    /// Transport answering the requests with `answers`, in order
+    #[derive(Debug)]
+    struct SequenceTransport {
+        answers: Mutex<Vec<HttpResponse>>,
+    }
+
+    impl SequenceTransport {
+        fn new(answers: Vec<(u16, Vec<u8>)>) -> Self {
+            let answers = answers
+                .into_iter()
+                .rev()
+                .map(|(status, body)| HttpResponse {
+                    status,
+                    headers: Vec::new(),
+                    body,
+                })
+                .collect();
+            SequenceTransport {
+                answers: Mutex::new(answers),
+            }
+        }
+    }
+
+    impl Transport for SequenceTransport {
+        fn get(&self, _request: &HttpRequest) -> std::result::Result<HttpResponse, TransportError> {
+            self.answers
+                .lock()
+                .unwrap()
+                .pop()
+                .ok_or_else(|| TransportError::Other("no answer left".to_string()))
+        }
+    }
+
+    /// Async transport answering as a `SequenceTransport`, recording its waits
+    #[derive(Debug)]
+    struct TimedTransport {
+        answers: SequenceTransport,
+        sleeps: Arc<Mutex<Vec<Duration>>>,
+    }
+
+    impl AsyncTransport for TimedTransport {
+        fn get<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+            Box::pin(async move { Transport::get(&self.answers, request) })
+        }
+
+        fn sleep(&self, duration: Duration) -> Option<transport::SleepFuture<'_>> {
+            self.sleeps.lock().unwrap().push(duration);
+            Some(Box::pin(async {}))
+        }
+    }
+
+    /// Run a future whose transports never wait
+    fn block_on<F: Future>(future: F) -> F::Output {
+        fn raw_waker() -> RawWaker {
+            fn clone(_: *const ()) -> RawWaker {
+                raw_waker()
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        let waker = unsafe { Waker::from_raw(raw_waker()) };
+        let mut future = Box::pin(future);
+        let mut context = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    fn set_mocks() -> Vec<mockito::Mock> {
+        let mut res = Vec::new();
+        let daily5_json = fs::read_to_string("assets/daily5.json").unwrap();
+        let _mdnokforbidden = mock("GET", "/forecasts/v1/daily/5day/12345")
+            .with_status(403)
+            .create();
+        res.push(_mdnokforbidden);
+        let _mdok = mock("GET", "/forecasts/v1/daily/5day/12345")
+            .with_status(200)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("details".into(), "true".into()),
+                Matcher::UrlEncoded("metric".into(), "true".into()),
+                Matcher::UrlEncoded("language".into(), "en-us".into()),
+            ]))
+            .with_body(&daily5_json)
+            .create();
+        res.push(_mdok);
+        let hourly12_json = fs::read_to_string("assets/hourly12.json").unwrap();
+        let _mhnokforbidden = mock("GET", "/forecasts/v1/hourly/12hour/12345")
+            .with_status(403)
+            .create();
+        res.push(_mhnokforbidden);
+        let _mhok = mock("GET", "/forecasts/v1/hourly/12hour/12345")
+            .with_status(200)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("details".into(), "true".into()),
+                Matcher::UrlEncoded("metric".into(), "true".into()),
+                Matcher::UrlEncoded("language".into(), "en-us".into()),
+            ]))
+            .with_body(&hourly12_json)
+            .create();
+        res.push(_mhok);
+
+        let quarters5_json = fs::read_to_string("assets/quarters5.json").unwrap();
+        let _mqok = mock("GET", "/forecasts/v1/daily/5day/quarters/12345")
+            .with_status(200)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("details".into(), "true".into()),
+                Matcher::UrlEncoded("metric".into(), "true".into()),
+                Matcher::UrlEncoded("language".into(), "en-us".into()),
+            ]))
+            .with_body(&quarters5_json)
+            .create();
+        res.push(_mqok);
+
+        let conditions_json = fs::read_to_string("assets/conditions.json").unwrap();
+        let _mcnokforbidden = mock("GET", "/currentconditions/v1/12345")
+            .with_status(403)
+            .create();
+        res.push(_mcnokforbidden);
+        let _mcok = mock("GET", "/currentconditions/v1/12345")
+            .with_status(200)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("details".into(), "true".into()),
+                Matcher::UrlEncoded("language".into(), "en-us".into()),
+            ]))
+            .with_body(&conditions_json)
+            .create();
+        res.push(_mcok);
+
+        for (path, fixture) in &[
+            (
+                "/forecasts/v1/daily/1day/67890",
+                "assets/daily1_details.json",
+            ),
+            (
+                "/forecasts/v1/hourly/1hour/67890",
+                "assets/hourly1_details.json",
+            ),
+            (
+                "/currentconditions/v1/67890",
+                "assets/conditions_details.json",
+            ),
+        ] {
+            let details_json = fs::read_to_string(fixture).unwrap();
+            let _mdetailsok = mock("GET", *path)
+                .with_status(200)
+                .match_query(Matcher::UrlEncoded("apikey".into(), "abcdefg".into()))
+                .with_body(&details_json)
+                .create();
+            res.push(_mdetailsok);
+        }
+
+        for (path, fixture) in &[
+            (
+                "/forecasts/v1/daily/5day/90001",
+                "assets/daily5_arctic.json",
+            ),
+            (
+                "/forecasts/v1/daily/5day/90002",
+                "assets/daily5_antarctic.json",
+            ),
+            (
+                "/forecasts/v1/hourly/12hour/90002",
+                "assets/hourly12_antarctic.json",
+            ),
+        ] {
+            let polar_json = fs::read_to_string(fixture).unwrap();
+            let _mpolarok = mock("GET", *path)
+                .with_status(200)
+                .match_query(Matcher::UrlEncoded("apikey".into(), "abcdefg".into()))
+                .with_body(&polar_json)
+                .create();
+            res.push(_mpolarok);
+        }
+
+        let aq_current_json = fs::read_to_string("assets/airquality_current.json").unwrap();
+        let _maqcnokforbidden = mock("GET", "/airquality/v2/currentconditions/12345")
+            .with_status(403)
+            .create();
+        res.push(_maqcnokforbidden);
+        let _maqcok = mock("GET", "/airquality/v2/currentconditions/12345")
+            .with_status(200)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("language".into(), "en-us".into()),
+            ]))
+            .with_body(&aq_current_json)
+            .create();
+        res.push(_maqcok);
+        let aq_hourly12_json = fs::read_to_string("assets/airquality_hourly12.json").unwrap();
+        let _maqhok = mock("GET", "/airquality/v2/forecasts/hourly/12hour/12345")
+            .with_status(200)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("language".into(), "en-us".into()),
+            ]))
+            .with_body(&aq_hourly12_json)
+            .create();
+        res.push(_maqhok);
+        let aq_daily4_json = fs::read_to_string("assets/airquality_daily4.json").unwrap();
+        let _maqdok = mock("GET", "/airquality/v2/forecasts/daily/4day/12345")
+            .with_status(200)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("language".into(), "en-us".into()),
+            ]))
+            .with_body(&aq_daily4_json)
+            .create();
+        res.push(_maqdok);
+
+        for (kind, fixture) in &[
+            ("actuals/2019/5/28", "assets/climo_actuals.json"),
+            ("normals/2019/5/28", "assets/climo_normals.json"),
+            ("records/2019/5/28", "assets/climo_records.json"),
+            ("summary/2019/5", "assets/climo_summary.json"),
+        ] {
+            let climo_json = fs::read_to_string(fixture).unwrap();
+            let _mclok = mock("GET", format!("/climo/v1/{}/12345", kind).as_str())
+                .with_status(200)
+                .match_query(Matcher::AllOf(vec![
+                    Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                    Matcher::UrlEncoded("details".into(), "true".into()),
+                    Matcher::UrlEncoded("metric".into(), "true".into()),
+                    Matcher::UrlEncoded("language".into(), "en-us".into()),
+                ]))
+                .with_body(&climo_json)
+                .create();
+            res.push(_mclok);
+        }
+
+        let imagery_json = fs::read_to_string("assets/imagery480.json").unwrap();
+        let _mimok = mock("GET", "/imagery/v1/maps/radsat/480x480/12345")
+            .with_status(200)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("language".into(), "en-us".into()),
+            ]))
+            .with_body(&imagery_json)
+            .create();
+        res.push(_mimok);
+        let _mframeok = mock("GET", "/images/radar_0.png")
+            .with_status(200)
+            .with_header("content-type", "image/png")
+            .with_body(&[0x89, b'P', b'N', b'G'][..])
+            .create();
+        res.push(_mframeok);
+
+        res
+    }
+
+    #[test]
+    fn test_search_locations_ok() {
+        let _mock = mock("GET", "/locations/v1/cities/search")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("apikey".into(), "abcdefg".into()),
+                Matcher::UrlEncoded("q".into(), "paris".into()),
+            ]))
+            .with_status(200)
+            .with_body_from_file("assets/locations_search.json")
+            .create();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, None, None);
+        let locations = client.search_locations("paris").unwrap();
+        assert_eq!(locations.len(), 2);
+        assert_eq!(locations[0].key, "623");
+        assert_eq!(locations[0].country.id, "FR");
+        assert_eq!(locations[1].administrative_area.localized_name, "Texas");
+        assert_eq!(locations[0].geo_position.elevation.metric.value, 35.0);
+    }
+
+    #[test]
+    fn test_daily_forecast_ok() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let res_forecasts = client.get_daily_forecasts(DailyPeriod::Days5);
+        let forecasts = res_forecasts.unwrap();
+        assert_eq!(forecasts.daily_forecasts[0].temperature.minimum.value, 5.4);
+    }
+    #[test]
+    fn test_daily_forecast_typed_values() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let forecasts = client.get_daily_forecasts(DailyPeriod::Days5).unwrap();
+        assert_eq!(forecasts.headline.category, HeadlineCategory::Rain);
+        let moon = &forecasts.daily_forecasts[0].moon;
+        assert_eq!(moon.phase, MoonPhase::WaningCrescent);
+        assert!((moon.illumination() - 0.31).abs() < 0.01);
+        assert_eq!(moon.phase.illumination(), Some(0.25));
+    }
+    #[test]
+    fn test_daily_forecast_air_and_pollen_summary() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let forecasts = client.get_daily_forecasts(DailyPeriod::Days5).unwrap();
+        let summary = forecasts.daily_forecasts[0].air_and_pollen_summary();
+        let air_quality = summary.air_quality.unwrap();
+        assert_eq!(air_quality.r#type, "Ozone");
+        assert_eq!(air_quality.category_value, 1);
+        assert_eq!(summary.uv_index.unwrap().category_value, 4);
+        assert_eq!(summary.tree.unwrap().r#type, "");
+        assert_eq!(summary.pollens_by_risk().len(), 4);
+    }
+    #[test]
+    fn test_daily_forecast_nok_forbidden() {
+        let _mocks = set_mocks();
+        let api_key = "bad_key".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let res_forecasts = client.get_daily_forecasts(DailyPeriod::Days5);
+        assert!(res_forecasts.is_err());
+    }
+    #[test]
+    fn test_daily_forecast_nok_badlocation() {
+        let _mocks = set_mocks();
+        let api_key = "bad_key".to_string();
+        let client = mock_client(api_key, Some(123456), None);
+        let res_forecasts = client.get_daily_forecasts(DailyPeriod::Days5);
+        assert!(res_forecasts.is_err());
+    }
+
+    #[test]
+    fn test_hourly_forecast_ok() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let res_forecasts = client.get_hourly_forecasts(HourlyPeriod::Hours12);
+        let forecasts = res_forecasts.unwrap();
+        assert_eq!(forecasts[11].temperature.value, 7.2);
+    }
+    #[test]
+    fn test_hourly_forecast_nok_forbidden() {
+        let _mocks = set_mocks();
+        let api_key = "bad_key".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let res_forecasts = client.get_hourly_forecasts(HourlyPeriod::Hours12);
+        assert!(res_forecasts.is_err());
+    }
+    #[test]
+    fn test_hourly_forecast_nok_badlocation() {
+        let _mocks = set_mocks();
+        let api_key = "bad_key".to_string();
+        let client = mock_client(api_key, Some(123456), None);
+        let res_forecasts = client.get_hourly_forecasts(HourlyPeriod::Hours12);
+        assert!(res_forecasts.is_err());
+    }
+    #[test]
+    fn test_hourly_forecast_nok_tier() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let mut client = mock_client(api_key, Some(12345), None);
+        assert!(client.get_hourly_forecasts(HourlyPeriod::Hours240).is_err());
+        client.set_subscription_tier(SubscriptionTier::Limited);
+        assert!(client.get_hourly_forecasts(HourlyPeriod::Hours24).is_err());
+        let error = client.get_daily_forecasts(DailyPeriod::Days10).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid daily period 10 days, expected a period of the Limited subscription tier"
+        );
+        assert!(client.get_hourly_forecasts(HourlyPeriod::Hours12).is_ok());
+    }
+    #[test]
+    fn test_periods_try_from() {
+        assert_eq!(HourlyPeriod::try_from(240).unwrap().hours(), 240);
+        assert_eq!(DailyPeriod::try_from(45).unwrap(), DailyPeriod::Days45);
+        assert_eq!(
+            HourlyPeriod::try_from(48).unwrap_err().to_string(),
+            "invalid hourly period 48, expected 1, 12, 24, 72, 120 or 240 hours"
+        );
+        assert!(DailyPeriod::try_from(7).is_err());
+        assert!(SubscriptionTier::Enterprise.allows_daily(DailyPeriod::Days45));
+        assert_eq!(AirQualityHourlyPeriod::try_from(48).unwrap().hours(), 48);
+        assert_eq!(
+            AirQualityDailyPeriod::try_from(4).unwrap(),
+            AirQualityDailyPeriod::Days4
+        );
+        assert!(AirQualityHourlyPeriod::try_from(120).is_err());
+        assert!(AirQualityDailyPeriod::try_from(5).is_err());
+    }
+    #[test]
+    fn test_weather_icons() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let forecasts = client.get_hourly_forecasts(HourlyPeriod::Hours12).unwrap();
+        assert_eq!(forecasts[0].weather_icon, WeatherIcon::PartlySunny);
+        let night = WeatherIcon::try_from(40).unwrap();
+        assert!(night.is_night() && !night.is_day());
+        assert_eq!(night.category(), IconCategory::Rain);
+        assert_eq!(night.wmo_code(), 80);
+        assert_eq!(
+            WeatherIcon::Sunny.image_url(),
+            "https://developer.accuweather.com/sites/default/files/01-s.png"
+        );
+        assert!(WeatherIcon::Fog.is_day() && WeatherIcon::Fog.is_night());
+        assert!(WeatherIcon::try_from(9).is_err());
+        assert!(WeatherIcon::try_from(45).is_err());
+    }
+
+    #[test]
+    fn test_quarter_forecast_ok() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let forecasts = client.get_quarter_forecasts(DailyPeriod::Days5).unwrap();
+        assert_eq!(forecasts.len(), 20);
+        assert_eq!(forecasts[0].name(), Some("Morning"));
+        assert_eq!(forecasts[3].name(), Some("Overnight"));
+        assert_eq!(forecasts[0].temperature.minimum.value, 6.1);
+    }
+    #[test]
+    fn test_quarter_forecast_nok_bad_period() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        assert!(client.get_quarter_forecasts(DailyPeriod::Days45).is_err());
+    }
+
+    #[test]
+    fn test_current_condition_ok() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let res_conditions = client.get_current_conditions();
+        let conditions = res_conditions.unwrap();
+        assert_eq!(conditions[0].temperature.metric.value, 27.9);
+        assert_eq!(
+            conditions[0].pressure_tendency.code,
+            PressureTendencyCode::Steady
+        );
+        assert_eq!(conditions[0].uv_index_text, UvIndexText::Moderate);
+        assert_eq!(conditions[0].precipitation_type, None);
+    }
+    #[test]
+    fn test_current_condition_nok_forbidden() {
+        let _mocks = set_mocks();
+        let api_key = "bad_key".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let res_conditions = client.get_current_conditions();
+        assert!(res_conditions.is_err());
+    }
+    #[test]
+    fn test_current_condition_nok_badlocation() {
+        let _mocks = set_mocks();
+        let api_key = "bad_key".to_string();
+        let client = mock_client(api_key, Some(123456), None);
+        let res_conditions = client.get_current_conditions();
+        assert!(res_conditions.is_err());
+    }
+
+    #[test]
+    fn test_daily_forecast_details() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(67890), None);
+        let forecasts = client.get_daily_forecasts(DailyPeriod::Day1).unwrap();
+        let forecast = &forecasts.daily_forecasts[0];
+        let day = &forecast.day;
+        assert_eq!(day.relative_humidity.as_ref().unwrap().average, 62);
+        let wbgt = day.wet_bulb_globe_temperature.as_ref().unwrap();
+        assert_eq!(wbgt.maximum.value, 17.2);
+        assert_eq!(day.hours_of_snow, 0.0);
+        assert_eq!(day.hours_of_ice, 0.0);
+        assert_eq!(day.precipitation_type, Some(PrecipitationType::Rain));
+        assert_eq!(day.solar_irradiance.as_ref().unwrap().unit, "W/m²");
+        assert_eq!(forecast.night.has_precipitation, Some(false));
+        assert_eq!(
+            forecast.real_feel_temperature.maximum.phrase.as_deref(),
+            Some("Cool")
+        );
+    }
+    #[test]
+    fn test_hourly_forecast_details() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(67890), None);
+        let forecasts = client.get_hourly_forecasts(HourlyPeriod::Hour1).unwrap();
+        let forecast = &forecasts[0];
+        assert_eq!(
+            forecast.real_feel_temperature_shade.as_ref().unwrap().value,
+            14.2
+        );
+        assert_eq!(forecast.evapotranspiration.as_ref().unwrap().value, 0.2);
+        assert_eq!(forecast.solar_irradiance.as_ref().unwrap().value, 402.5);
+        assert_eq!(forecast.indoor_relative_humidity, Some(44));
+        assert_eq!(forecast.wind_gust.speed.value, 25.9);
+        assert_eq!(forecast.wind_gust.direction.as_ref().unwrap().english, "NW");
+    }
+    #[test]
+    fn test_current_condition_details() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(67890), None);
+        let conditions = client.get_current_conditions().unwrap();
+        assert_eq!(conditions[0].indoor_relative_humidity, Some(38));
+        let wbgt = conditions[0].wet_bulb_globe_temperature.as_ref().unwrap();
+        assert_eq!(wbgt.metric.value, 24.6);
+        let summary = &conditions[0].precipitation_summary;
+        assert_eq!(summary.past18_hours.metric.value, 0.0);
+    }
+
+    #[test]
+    fn test_daily_forecast_arctic_polar_day() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(90001), None);
+        let forecasts = client.get_daily_forecasts(DailyPeriod::Days5).unwrap();
+        let forecast = &forecasts.daily_forecasts[0];
+        assert!(forecast.sun.rise.is_none() && forecast.sun.epoch_set.is_none());
+        assert!(forecast.hours_of_sun > 20.0);
+        assert!(forecast.moon.set.is_none() && forecast.moon.rise.is_some());
+        assert!(forecasts.daily_forecasts[1].moon.rise.is_none());
+    }
+    #[test]
+    fn test_daily_forecast_antarctic_polar_night() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(90002), None);
+        let forecasts = client.get_daily_forecasts(DailyPeriod::Days5).unwrap();
+        let forecast = &forecasts.daily_forecasts[0];
+        assert!(forecast.sun.rise.is_none() && forecast.sun.set.is_none());
+        assert_eq!(forecast.hours_of_sun, 0.0);
+        let hourly = client.get_hourly_forecasts(HourlyPeriod::Hours12).unwrap();
+        assert!(hourly[1].ceiling.is_none());
+        assert!(hourly[0].ceiling.is_some());
+    }
+    #[test]
+    fn test_lenient_parsing() {
+        let mut hourly: Value =
+            serde_json::from_str(&fs::read_to_string("assets/hourly1_details.json").unwrap())
+                .unwrap();
+        hourly[0]["IndoorRelativeHumidity"] = Value::String("44%".to_string());
+        hourly[0]["Evapotranspiration"]["Value"] = Value::Null;
+        let _mock = mock("GET", "/forecasts/v1/hourly/1hour/90003")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(hourly.to_string())
+            .create();
+        let api_key = "abcdefg".to_string();
+        let mut client = mock_client(api_key, Some(90003), None);
+        assert!(client.get_hourly_forecasts(HourlyPeriod::Hour1).is_err());
+        assert!(client.take_lenient_issues().is_empty());
+
+        client.set_lenient(true);
+        let forecasts = client.get_hourly_forecasts(HourlyPeriod::Hour1).unwrap();
+        assert!(forecasts[0].indoor_relative_humidity.is_none());
+        assert!(forecasts[0].evapotranspiration.is_none());
+        assert_eq!(forecasts[0].temperature.value, 15.1);
+        let issues = client.take_lenient_issues();
+        assert_eq!(issues.len(), 2);
+        let evapotranspiration = issues
+            .iter()
+            .find(|i| i.path == "[0].Evapotranspiration.Value")
+            .unwrap();
+        assert_eq!(evapotranspiration.dropped, "[0].Evapotranspiration");
+        assert!(client.take_lenient_issues().is_empty());
+
+        hourly[0]["Temperature"] = Value::Null;
+        let _mock = mock("GET", "/forecasts/v1/hourly/1hour/90003")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(hourly.to_string())
+            .create();
+        assert!(client.get_hourly_forecasts(HourlyPeriod::Hour1).is_err());
+    }
+
+    #[test]
+    fn test_raw_answer_and_extra_fields() {
+        let mut hourly: Value =
+            serde_json::from_str(&fs::read_to_string("assets/hourly1_details.json").unwrap())
+                .unwrap();
+        hourly[0]["NewField"] = Value::String("new".to_string());
+        let _mock = mock("GET", "/forecasts/v1/hourly/1hour/90004")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(hourly.to_string())
+            .create();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(90004), None);
+        let (raw, forecasts) = client
+            .get_hourly_forecasts_raw(HourlyPeriod::Hour1)
+            .unwrap();
+        assert_eq!(raw, hourly);
+        assert_eq!(forecasts[0].extra["NewField"], "new");
+        assert!(!forecasts[0].extra.contains_key("Temperature"));
     }
 
     #[test]
-    fn test_hourly_forecast_ok() {
+    fn test_parse_error_keeps_payload() {
+        let mut hourly: Value =
+            serde_json::from_str(&fs::read_to_string("assets/hourly1_details.json").unwrap())
+                .unwrap();
+        hourly[0]["Temperature"] = Value::Null;
+        let _mock = mock("GET", "/forecasts/v1/hourly/1hour/90005")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(hourly.to_string())
+            .create();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(90005), None);
+        let error = client
+            .get_hourly_forecasts(HourlyPeriod::Hour1)
+            .unwrap_err();
+        let error = error.downcast_ref::<AccuweatherParseError>().unwrap();
+        assert_eq!(error.payload, hourly);
+    }
+
+    #[test]
+    fn test_answer_metadata() {
+        let _mock = mock("GET", "/currentconditions/v1/90006")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("RateLimit-Remaining", "42")
+            .with_header("Expires", "Sat, 13 Jul 2019 10:30:00 GMT")
+            .with_header("X-Request-Id", "abc-123")
+            .with_body_from_file("assets/conditions_details.json")
+            .create();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(90006), None);
+        let response = client.get_current_conditions_with_meta().unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.rate_limit_remaining, Some(42));
+        assert_eq!(
+            response.expires.as_deref(),
+            Some("Sat, 13 Jul 2019 10:30:00 GMT")
+        );
+        assert_eq!(response.request_id.as_deref(), Some("abc-123"));
+        assert!(!response.url.contains("apikey"));
+        assert!(!response.url.contains("abcdefg"));
+        assert_eq!(response.body.len(), 1);
+    }
+
+    #[test]
+    fn test_api_key_redacted() {
+        let api_key = "s3cr3t-k3y".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        assert_eq!(client.key_usage()[0].key.expose(), "s3cr3t-k3y");
+        assert_eq!(client.key_usage()[0].key.to_string(), "REDACTED");
+        assert!(!format!("{:?}", client).contains("s3cr3t-k3y"));
+        assert!(!format!("{:#?}", client).contains("s3cr3t-k3y"));
+
+        let error = client.get_current_conditions().unwrap_err();
+        assert!(error.to_string().contains("apikey=REDACTED"));
+        assert!(!error.to_string().contains("s3cr3t-k3y"));
+        assert!(!format!("{:?}", error).contains("s3cr3t-k3y"));
+        let error = error.downcast_ref::<AccuweatherHttpError>().unwrap();
+        assert_eq!(error.status, Some(501));
+    }
+
+    #[test]
+    fn test_form_encoded_api_key_redacted() {
+        let api_key = "s3cr+t/k3y=".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let error = client.get_current_conditions().unwrap_err();
+        assert!(error.to_string().contains("apikey=REDACTED"));
+        assert!(!error.to_string().contains("s3cr%2Bt%2Fk3y%3D"));
+        assert!(!error.to_string().contains("s3cr"));
+    }
+
+    #[test]
+    fn test_auth_header() {
+        let _mock = mock("GET", "/currentconditions/v1/90007")
+            .match_header("Authorization", "Bearer abcdefg")
+            .match_query(Matcher::Exact("details=true&language=en-us".to_string()))
+            .with_status(200)
+            .with_body_from_file("assets/conditions_details.json")
+            .create();
+        let api_key = "abcdefg".to_string();
+        let mut client = mock_client(api_key, Some(90007), None);
+        assert!(client.get_current_conditions().is_err());
+        client.set_auth_mode(AuthMode::Header);
+        let response = client.get_current_conditions_with_meta().unwrap();
+        assert_eq!(response.body.len(), 1);
+        assert!(!response.url.contains("abcdefg"));
+    }
+
+    #[test]
+    fn test_config_from_env_variables() {
+        let vars: HashMap<&str, &str> = [
+            ("ACCUWEATHER_API_KEY", "abcdefg"),
+            ("ACCUWEATHER_LOCATION", "12345"),
+            ("ACCUWEATHER_UNITS", "imperial"),
+            ("ACCUWEATHER_AUTH_MODE", "header"),
+            ("ACCUWEATHER_RETRIES", "2"),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        let lookup = |name: &str| vars.get(name).map(|value| value.to_string());
+        let config = Config::from_lookup(lookup).unwrap();
+        assert_eq!(config.location, Some(12345));
+        assert_eq!(config.units, Some(Units::Imperial));
+        let client = config.build().unwrap();
+        assert_eq!(client.key_usage()[0].key.expose(), "abcdefg");
+        assert_eq!(client.auth_mode, AuthMode::Header);
+        assert_eq!(client.retries, 2);
+        assert_eq!(client.language, "en-us");
+
+        let error = Config::from_lookup(|_| None).unwrap_err();
+        assert_eq!(
+            error,
+            ConfigError::Missing("ACCUWEATHER_API_KEY".to_string())
+        );
+        let error = Config::from_lookup(|name| match name {
+            "ACCUWEATHER_LOCATION" => Some("paris".to_string()),
+            _ => lookup(name),
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("ACCUWEATHER_LOCATION"));
+        let error = Config::from_lookup(|name| match name {
+            "ACCUWEATHER_UNITS" => Some("kelvin".to_string()),
+            _ => lookup(name),
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("ACCUWEATHER_UNITS"));
+        let error = Config::from_lookup(|name| match name {
+            "ACCUWEATHER_BASE_URL" => Some("nowhere".to_string()),
+            _ => lookup(name),
+        })
+        .unwrap_err();
+        match error {
+            ConfigError::Invalid { setting, .. } => assert_eq!(setting, "ACCUWEATHER_BASE_URL"),
+            error => panic!("unexpected error {}", error),
+        }
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        let config = Config::from_toml(
+            "api_key = \"abcdefg\"\nlocation = 12345\nsubscription_tier = \"limited\"\ntimeout_secs = 5",
+        )
+        .unwrap();
+        assert_eq!(config.subscription_tier, Some(SubscriptionTier::Limited));
+        assert_eq!(config.timeout_secs, Some(5));
+        assert!(config.build().is_ok());
+
+        let error = Config::from_toml("location = 12345").unwrap_err();
+        assert_eq!(error, ConfigError::Missing("api_key".to_string()));
+        let error = Config::from_toml("api_key = \"abcdefg\"\nretries = \"many\"").unwrap_err();
+        match error {
+            ConfigError::Invalid { setting, .. } => assert_eq!(setting, "retries"),
+            error => panic!("unexpected error {}", error),
+        }
+        let error = Config::from_toml("api_key = ").unwrap_err();
+        match error {
+            ConfigError::Parse(message) => assert!(!message.is_empty()),
+            error => panic!("unexpected error {}", error),
+        }
+        let error = Config::from_toml("api_key = \"abcdefg\"\ncache = true").unwrap_err();
+        assert!(error.to_string().contains("cache"));
+        let error = Config::from_toml("api_key = \"abcdefg\"\nbase_url = \"nowhere\"").unwrap_err();
+        assert!(error.to_string().contains("base_url"));
+        let config = Config {
+            api_key: Some(ApiKey::from("abcdefg")),
+            base_url: Some("nowhere".to_string()),
+            ..Config::default()
+        };
+        assert!(config.build().unwrap_err().to_string().contains("base_url"));
+        let error = Accuweather::from_config_file("assets/missing.toml").unwrap_err();
+        assert!(error.to_string().contains("assets/missing.toml"));
+    }
+
+    #[test]
+    fn test_imperial_units() {
+        let _mock = mock("GET", "/forecasts/v1/daily/5day/90008")
+            .match_query(Matcher::UrlEncoded("metric".into(), "false".into()))
+            .with_status(200)
+            .with_body_from_file("assets/daily5.json")
+            .create();
+        let api_key = "abcdefg".to_string();
+        let mut client = mock_client(api_key, Some(90008), None);
+        assert!(client.get_daily_forecasts(DailyPeriod::Days5).is_err());
+        client.set_units(Units::Imperial);
+        assert!(client.get_daily_forecasts(DailyPeriod::Days5).is_ok());
+    }
+
+    #[test]
+    fn test_retries() {
+        let mock = mock("GET", "/currentconditions/v1/90009")
+            .match_query(Matcher::Any)
+            .with_status(500)
+            .expect(3)
+            .create();
+        let api_key = "abcdefg".to_string();
+        let mut client = mock_client(api_key, Some(90009), None);
+        client.set_retries(2);
+        let error = client.get_current_conditions().unwrap_err();
+        let error = error.downcast_ref::<AccuweatherHttpError>().unwrap();
+        assert_eq!(error.status, Some(500));
+        mock.assert();
+    }
+
+    fn key_mock(location: i32, key: &str, status: usize, body: &str) -> mockito::Mock {
+        mock(
+            "GET",
+            format!("/currentconditions/v1/{}", location).as_str(),
+        )
+        .match_query(Matcher::UrlEncoded("apikey".into(), key.into()))
+        .with_status(status)
+        .with_body(body)
+        .create()
+    }
+
+    #[test]
+    fn test_key_failover() {
+        let conditions = fs::read_to_string("assets/conditions_details.json").unwrap();
+        let quota = r#"{"Code":"ServiceUnavailable","Message":"The allowed number of requests has been exceeded."}"#;
+        let _mocks = [
+            key_mock(90010, "refused", 401, ""),
+            key_mock(90010, "spent", 503, quota),
+            key_mock(90010, "good", 200, &conditions),
+        ];
+        let api_key = "refused".to_string();
+        let mut client = mock_client(api_key, Some(90010), None);
+        let keys = vec!["refused".into(), "spent".into(), "good".into()];
+        client
+            .set_api_keys(keys, KeyStrategy::PrimaryWithFallback)
+            .unwrap();
+        assert!(client.get_current_conditions().is_ok());
+        assert!(client.get_current_conditions().is_ok());
+        let usage = client.key_usage();
+        assert_eq!(usage[0].state, KeyState::Rejected(401));
+        assert_eq!((usage[0].requests, usage[0].failures), (1, 1));
+        match usage[1].state {
+            KeyState::Exhausted { .. } => (),
+            state => panic!("unexpected state {:?}", state),
+        }
+        assert_eq!(usage[1].rate_limit_remaining, Some(0));
+        assert_eq!((usage[2].requests, usage[2].failures), (2, 0));
+        assert_eq!(usage[2].state, KeyState::Active);
+
+        client.reset_key_states();
+        assert!(client
+            .key_usage()
+            .iter()
+            .all(|usage| usage.state == KeyState::Active));
+        client
+            .set_api_keys(vec!["refused".into()], KeyStrategy::default())
+            .unwrap();
+        let error = client.get_current_conditions().unwrap_err();
+        let error = error.downcast_ref::<AccuweatherHttpError>().unwrap();
+        assert_eq!(error.status, Some(401));
+        assert!(client
+            .set_api_keys(Vec::new(), KeyStrategy::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_key_strategies() {
+        let conditions = fs::read_to_string("assets/conditions_details.json").unwrap();
+        let _mocks = [
+            key_mock(90011, "first", 200, &conditions),
+            key_mock(90011, "second", 200, &conditions),
+        ];
+        let api_key = "first".to_string();
+        let mut client = mock_client(api_key, Some(90011), None);
+        client
+            .set_api_keys(
+                vec!["first".into(), "second".into()],
+                KeyStrategy::RoundRobin,
+            )
+            .unwrap();
+        for _ in 0..4 {
+            client.get_current_conditions().unwrap();
+        }
+        let usage = client.key_usage();
+        assert_eq!((usage[0].requests, usage[1].requests), (2, 2));
+
+        client
+            .set_api_keys(
+                vec!["first".into(), "second".into()],
+                KeyStrategy::LeastUsed,
+            )
+            .unwrap();
+        for _ in 0..3 {
+            client.get_current_conditions().unwrap();
+        }
+        let usage = client.key_usage();
+        assert_eq!((usage[0].requests, usage[1].requests), (2, 1));
+    }
+
+    #[test]
+    fn test_batch_requests() {
+        let conditions = fs::read_to_string("assets/conditions_details.json").unwrap();
+        let _mocks: Vec<mockito::Mock> = (90020..90030)
+            .map(|location| {
+                mock(
+                    "GET",
+                    format!("/currentconditions/v1/{}", location).as_str(),
+                )
+                .match_query(Matcher::Any)
+                .with_status(if location == 90025 { 500 } else { 200 })
+                .with_body(&conditions)
+                .create()
+            })
+            .collect();
+        let api_key = "abcdefg".to_string();
+        let mut client = mock_client(api_key, None, None);
+        client.set_parallelism(3);
+        let locations: Vec<i32> = (90020..90030).collect();
+        let results = client.get_current_conditions_batch(&locations);
+        assert_eq!(results.len(), 10);
+        assert!(results[&90025].is_err());
+        assert_eq!(results.values().filter(|result| result.is_ok()).count(), 9);
+        assert_eq!(results[&90020].as_ref().unwrap()[0].relative_humidity, 43);
+        assert!(client.get_current_conditions().is_err());
+
+        let results = client.get_hourly_forecasts_batch(&[12345], HourlyPeriod::Hours240);
+        assert!(results[&12345]
+            .as_ref()
+            .unwrap_err()
+            .is::<AccuweatherInvalidParameterError>());
+    }
+
+    #[test]
+    fn test_batch_stops_when_quota_exceeded() {
+        let quota = r#"{"Code":"ServiceUnavailable","Message":"The allowed number of requests has been exceeded."}"#;
+        let mock = mock("GET", "/currentconditions/v1/90030")
+            .match_query(Matcher::Any)
+            .with_status(503)
+            .with_body(quota)
+            .expect(1)
+            .create();
+        let api_key = "abcdefg".to_string();
+        let mut client = mock_client(api_key, None, None);
+        client.set_parallelism(1);
+        let results = client.get_current_conditions_batch(&[90030, 90031, 90032]);
+        assert!(!results[&90030]
+            .as_ref()
+            .unwrap_err()
+            .is::<AccuweatherQuotaError>());
+        assert!(results[&90031]
+            .as_ref()
+            .unwrap_err()
+            .is::<AccuweatherQuotaError>());
+        assert!(results[&90032].is_err());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_watch_events() {
+        use crate::watch::{WatchEvent, WatchRules, Watcher};
+        let conditions: Value =
+            serde_json::from_str(&fs::read_to_string("assets/conditions_details.json").unwrap())
+                .unwrap();
+        let mut rainy = conditions[0].clone();
+        rainy["HasPrecipitation"] = Value::from(true);
+        rainy["PrecipitationType"] = Value::from("Rain");
+        rainy["Temperature"]["Metric"]["Value"] = Value::from(22.5);
+        fn parse<T: DeserializeOwned>(value: &Value) -> T {
+            serde_json::from_value(value.clone()).unwrap()
+        }
+        let mut watcher = Watcher::new(WatchRules {
+            temperature_thresholds: vec![25.0, 30.0],
+            ..Default::default()
+        });
+        assert!(watcher
+            .update_conditions(623, parse(&conditions[0]))
+            .is_empty());
+        assert_eq!(
+            watcher.update_conditions(623, parse(&rainy)),
+            vec![
+                WatchEvent::PrecipitationStarted {
+                    location: 623,
+                    precipitation_type: Some(PrecipitationType::Rain),
+                },
+                WatchEvent::TemperatureCrossed {
+                    location: 623,
+                    threshold: 25.0,
+                    temperature: 22.5,
+                    rising: false,
+                },
+            ]
+        );
+        assert!(watcher.update_conditions(623, parse(&rainy)).is_empty());
+        assert_eq!(
+            watcher.update_conditions(623, parse(&conditions[0])),
+            vec![
+                WatchEvent::PrecipitationStopped { location: 623 },
+                WatchEvent::TemperatureCrossed {
+                    location: 623,
+                    threshold: 25.0,
+                    temperature: 27.9,
+                    rising: true,
+                },
+            ]
+        );
+
+        let daily: Value =
+            serde_json::from_str(&fs::read_to_string("assets/daily5.json").unwrap()).unwrap();
+        let mut updated = daily.clone();
+        updated["Headline"]["Text"] = Value::from("Orages attendus Mercredi");
+        updated["DailyForecasts"][1]["Day"]["RainProbability"] = Value::from(90);
+        updated["DailyForecasts"][2]["Night"]["RainProbability"] = Value::from(35);
+        assert!(watcher
+            .update_daily_forecasts(623, parse(&daily))
+            .is_empty());
+        let events = watcher.update_daily_forecasts(623, parse(&updated));
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0],
+            WatchEvent::NewHeadline {
+                location: 623,
+                text: "Orages attendus Mercredi".to_string(),
+                category: HeadlineCategory::Rain,
+                severity: 5,
+            }
+        );
+        assert_eq!(
+            events[1],
+            WatchEvent::RainProbabilityChanged {
+                location: 623,
+                date: "2019-05-29T07:00:00+02:00".to_string(),
+                from: 56,
+                to: 90,
+            }
+        );
+        assert_eq!(
+            watcher.last_daily_forecasts(623).unwrap().headline.text,
+            "Orages attendus Mercredi"
+        );
+    }
+
+    #[test]
+    fn test_watch_poll_async() {
+        use crate::watch::{WatchEvent, WatchRules, Watcher};
+        let mut conditions: Value =
+            serde_json::from_str(&fs::read_to_string("assets/conditions_details.json").unwrap())
+                .unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let transport = |conditions: &Value| FixedTransport {
+            status: 200,
+            body: conditions.to_string(),
+            requests: Arc::clone(&requests),
+        };
+        let mut client = mock_client("abcdefg".to_string(), None, None);
+        client.set_base_url("http://example.invalid".to_string());
+        let mut watcher = Watcher::new(WatchRules {
+            daily_forecasts: None,
+            ..Default::default()
+        });
+        client.set_async_transport(transport(&conditions));
+        let events = block_on(watcher.poll_async(&client, &[623]));
+        assert!(events.is_empty());
+        conditions[0]["HasPrecipitation"] = Value::from(true);
+        client.set_async_transport(transport(&conditions));
+        let events = block_on(watcher.poll_async(&client, &[623]));
+        assert_eq!(
+            events,
+            vec![WatchEvent::PrecipitationStarted {
+                location: 623,
+                precipitation_type: None,
+            }]
+        );
+        client.set_async_transport(FixedTransport {
+            status: 404,
+            body: String::new(),
+            requests: Arc::clone(&requests),
+        });
+        match &block_on(watcher.poll_async(&client, &[623]))[..] {
+            [WatchEvent::Failed { location: 623, .. }] => {}
+            events => panic!("unexpected events {:?}", events),
+        }
+        assert_eq!(requests.lock().unwrap().len(), 3);
+        assert!(watcher.last_conditions(623).unwrap().has_precipitation);
+    }
+
+    #[test]
+    fn test_watch_spawn_stops() {
+        use crate::watch::{WatchRules, Watcher};
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut client = mock_client("abcdefg".to_string(), None, None);
+        client.set_transport(FixedTransport {
+            status: 200,
+            body: fs::read_to_string("assets/conditions_details.json").unwrap(),
+            requests: Arc::clone(&requests),
+        });
+        let watcher = Watcher::new(WatchRules {
+            daily_forecasts: None,
+            ..Default::default()
+        });
+        // stable conditions send no event, the handle stops the thread all the same
+        let watch = watcher.spawn(client, vec![623], Duration::from_secs(3600));
+        while requests.lock().unwrap().is_empty() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        watch.stop();
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_schedule_plan() {
+        use crate::schedule::{Endpoint, Scheduler};
+        use std::time::UNIX_EPOCH;
+        // 2019-05-28 12:00 UTC
+        let noon = UNIX_EPOCH + Duration::from_secs(1_559_044_800);
+        let mut scheduler = Scheduler::new(10);
+        scheduler.add_task(&[623, 335951], Endpoint::CurrentConditions, 3);
+        scheduler.add_task(&[623], Endpoint::DailyForecasts(DailyPeriod::Days5), 1);
+        scheduler.set_active_hours(Some((8, 20)));
+        let plan = scheduler.plan(noon);
+        assert_eq!(plan.len(), 10);
+        assert_eq!(plan[1].after, Duration::from_secs(4320));
+        let daily: Vec<usize> = (0..plan.len())
+            .filter(|&i| plan[i].endpoint != Endpoint::CurrentConditions)
+            .collect();
+        assert_eq!(daily, vec![2, 9]);
+        assert_eq!((plan[0].location, plan[1].location), (623, 335951));
+
+        scheduler.record_call(noon, 4, Some(3));
+        assert_eq!(scheduler.calls_left(noon), 3);
+        assert_eq!(scheduler.plan(noon).len(), 3);
+        let tomorrow = noon + Duration::from_secs(24 * 60 * 60);
+        assert_eq!(scheduler.calls_left(tomorrow), 10);
+    }
+
+    #[test]
+    fn test_schedule_run() {
+        use crate::schedule::{Answer, Endpoint, Scheduler};
+        let _mock = mock("GET", "/currentconditions/v1/90033")
+            .match_query(Matcher::Any)
+            .with_header("RateLimit-Remaining", "7")
+            .with_body(fs::read_to_string("assets/conditions_details.json").unwrap())
+            .create();
+        let client = mock_client("abcdefg".to_string(), None, None);
+        let mut scheduler = Scheduler::new(50);
+        scheduler.add_task(&[90033], Endpoint::CurrentConditions, 1);
+        let mut answers = Vec::new();
+        scheduler.run(&client, |call, answer| {
+            answers.push((call.location, answer));
+            false
+        });
+        assert_eq!(answers.len(), 1);
+        match &answers[0] {
+            (90033, Ok(Answer::CurrentConditions(conditions))) => {
+                assert_eq!(conditions[0].relative_humidity, 43)
+            }
+            other => panic!("unexpected answer {:?}", other),
+        }
+        assert_eq!(scheduler.calls_today(), 1);
+        assert_eq!(scheduler.calls_left(SystemTime::now()), 7);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_stub_server_fixtures() {
+        use crate::testing::StubServer;
+        let server = StubServer::start().unwrap();
+        let client = server.client(Some(623));
+        assert_eq!(client.search_locations("paris").unwrap()[0].key, "623");
+        assert!(client.get_hourly_forecasts(HourlyPeriod::Hour1).is_ok());
+        assert!(client.get_hourly_forecasts(HourlyPeriod::Hours12).is_ok());
+        assert!(client.get_daily_forecasts(DailyPeriod::Day1).is_ok());
+        assert!(client.get_daily_forecasts(DailyPeriod::Days5).is_ok());
+        assert!(client.get_quarter_forecasts(DailyPeriod::Days5).is_ok());
+        assert!(client.get_current_conditions().is_ok());
+        assert!(client.get_air_quality_current_conditions().is_ok());
+        assert!(client
+            .get_air_quality_hourly_forecasts(AirQualityHourlyPeriod::Hours12)
+            .is_ok());
+        assert!(client
+            .get_air_quality_daily_forecasts(AirQualityDailyPeriod::Days4)
+            .is_ok());
+        assert!(client.get_climo_actuals(2019, 5, 28).is_ok());
+        assert!(client.get_climo_normals(2019, 5, 28).is_ok());
+        assert!(client.get_climo_records(2019, 5, 28).is_ok());
+        assert!(client.get_climo_month_summary(2019, 5).is_ok());
+        let frames = client.get_radar_frames("480x480").unwrap();
+        let image = client.download_imagery_frame(&frames[0]).unwrap();
+        assert_eq!(&image[1..4], b"PNG");
+        assert_eq!(server.requests().len(), 16);
+        assert!(server
+            .requests()
+            .iter()
+            .all(|path| !path.contains(crate::testing::API_KEY)));
+    }
+
+    /// Status of the error of a request to a stub server answering with `failure`
+    #[cfg(feature = "testing")]
+    fn stub_failure_status(failure: crate::testing::Failure) -> Option<u16> {
+        let server = crate::testing::StubServer::start().unwrap();
+        let client = server.client(Some(623));
+        server.set_failure(Some(failure));
+        let error = client.get_current_conditions().unwrap_err();
+        error.downcast_ref::<AccuweatherHttpError>().unwrap().status
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_stub_server_unauthorized() {
+        use crate::testing::Failure;
+        assert_eq!(stub_failure_status(Failure::Unauthorized), Some(401));
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_stub_server_forbidden() {
+        use crate::testing::Failure;
+        assert_eq!(stub_failure_status(Failure::Forbidden), Some(403));
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_stub_server_server_error() {
+        use crate::testing::Failure;
+        assert_eq!(stub_failure_status(Failure::ServerError), Some(500));
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_stub_server_quota_exceeded() {
+        use crate::testing::Failure;
+        assert_eq!(stub_failure_status(Failure::QuotaExceeded), Some(503));
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_stub_server_malformed_json() {
+        use crate::testing::Failure;
+        assert_eq!(stub_failure_status(Failure::MalformedJson), None);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_stub_server_quota() {
+        use crate::testing::StubServer;
+        let server = StubServer::start().unwrap();
+        let client = server.client(Some(623));
+        server.set_quota(Some(1));
+        let response = client.get_current_conditions_with_meta().unwrap();
+        assert_eq!(response.rate_limit_remaining, Some(0));
+        let error = client.get_current_conditions().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AccuweatherHttpError>().unwrap().status,
+            Some(503)
+        );
+        server.set_quota(None);
+        assert!(client.get_current_conditions().is_ok());
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_stub_server_latency() {
+        use crate::testing::StubServer;
+        let server = StubServer::start().unwrap();
+        let client = server.client(Some(623));
+        server.set_latency(Duration::from_millis(100));
+        let response = client.get_current_conditions_with_meta().unwrap();
+        assert!(response.elapsed >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_cassette_record_and_replay() {
+        use crate::cassette::{Cassette, CassetteError};
+        let _ok = mock("GET", "/currentconditions/v1/90034")
+            .match_query(Matcher::Any)
+            .with_header("RateLimit-Remaining", "12")
+            .with_body(fs::read_to_string("assets/conditions_details.json").unwrap())
+            .create();
+        let _error = mock("GET", "/currentconditions/v1/90035")
+            .match_query(Matcher::Any)
+            .with_status(500)
+            .create();
+        let path = std::env::temp_dir().join(format!("accuweather-{}.json", std::process::id()));
+        let mut client = mock_client("abcdefg".to_string(), Some(90034), None);
+        client.set_cassette(Some(Cassette::record(&path)));
+        client.get_current_conditions().unwrap();
+        client.set_location(Some(90035));
+        assert!(client.get_current_conditions().is_err());
+        client.save_cassette().unwrap();
+        let recorded = fs::read_to_string(&path).unwrap();
+        assert!(!recorded.contains("abcdefg"));
+        assert!(recorded.contains("/currentconditions/v1/90034?details=true&language=en-us"));
+
+        let mut client = mock_client("hijklmn".to_string(), Some(90034), None);
+        client.set_base_url("http://127.0.0.1:9".to_string());
+        client.set_cassette(Some(Cassette::replay(&path).unwrap()));
+        let response = client.get_current_conditions_with_meta().unwrap();
+        assert_eq!(response.body[0].relative_humidity, 43);
+        assert_eq!(response.rate_limit_remaining, Some(12));
+        client.set_location(Some(90035));
+        let error = client.get_current_conditions().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AccuweatherHttpError>().unwrap().status,
+            Some(500)
+        );
+        assert!(client.cassette().unwrap().unplayed().is_empty());
+        client.set_location(Some(90036));
+        let error = client.get_current_conditions().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CassetteError>(),
+            Some(&CassetteError::Unmatched {
+                request: "/currentconditions/v1/90036?details=true&language=en-us".to_string()
+            })
+        );
+        // each answer is replayed once, a repeated request fails
+        client.set_location(Some(90034));
+        let error = client.get_current_conditions().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CassetteError>(),
+            Some(&CassetteError::Unmatched {
+                request: "/currentconditions/v1/90034?details=true&language=en-us".to_string()
+            })
+        );
+        // and so does a retry that wasn't recorded
+        client.set_cassette(Some(Cassette::replay(&path).unwrap()));
+        client.set_retries(1);
+        client.set_location(Some(90035));
+        let error = client.get_current_conditions().unwrap_err();
+        assert!(error.downcast_ref::<CassetteError>().is_some());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_cassette_replays_attempts() {
+        use crate::cassette::Cassette;
+        let conditions = fs::read("assets/conditions_details.json").unwrap();
+        let path =
+            std::env::temp_dir().join(format!("accuweather-{}-attempts.json", std::process::id()));
+        let mut client = Accuweather::new("refused".to_string(), Some(623), None);
+        client.set_base_url("http://example.invalid".to_string());
+        client
+            .set_api_keys(
+                vec!["refused".into(), "good".into()],
+                KeyStrategy::PrimaryWithFallback,
+            )
+            .unwrap();
+        client.set_retries(1);
+        client.set_transport(SequenceTransport::new(vec![
+            (401, Vec::new()),
+            (500, Vec::new()),
+            (200, conditions),
+        ]));
+        client.set_cassette(Some(Cassette::record(&path)));
+        assert!(client.get_current_conditions().is_ok());
+        let statuses: Vec<u16> = client
+            .cassette()
+            .unwrap()
+            .interactions()
+            .iter()
+            .map(|interaction| interaction.status)
+            .collect();
+        assert_eq!(statuses, vec![401, 500, 200]);
+        client.save_cassette().unwrap();
+
+        client.set_transport(SequenceTransport::new(Vec::new()));
+        client.reset_key_states();
+        client.set_cassette(Some(Cassette::replay(&path).unwrap()));
+        let conditions = client.get_current_conditions().unwrap();
+        assert_eq!(conditions[0].relative_humidity, 43);
+        assert!(client.cassette().unwrap().unplayed().is_empty());
+        assert_eq!(client.key_usage()[0].state, KeyState::Rejected(401));
+
+        client.reset_key_states();
+        client.set_retries(0);
+        client.set_cassette(Some(Cassette::replay(&path).unwrap()));
+        let error = client.get_current_conditions().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AccuweatherHttpError>().unwrap().status,
+            Some(500)
+        );
+        // the answer after the retry is left
+        assert_eq!(client.cassette().unwrap().unplayed().len(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_cassette_imagery_frames() {
+        use crate::cassette::{Cassette, CassetteError};
+        let image = vec![0x89, b'P', b'N', b'G', 0x00, 0xff];
+        let frame = |url: &str| ImageryFrame {
+            kind: ImageryType::Radar,
+            url: url.to_string(),
+            date: "2019-08-31T12:30:00+00:00".to_string(),
+            size: "480x480".to_string(),
+        };
+        let path =
+            std::env::temp_dir().join(format!("accuweather-{}-frames.json", std::process::id()));
+        let mut client = Accuweather::new("abcdefg".to_string(), Some(623), None);
+        client.set_transport(SequenceTransport::new(vec![(200, image.clone())]));
+        client.set_cassette(Some(Cassette::record(&path)));
+        let recorded = client
+            .download_imagery_frame(&frame("http://example.invalid/radar_0.png"))
+            .unwrap();
+        assert_eq!(recorded, image);
+        assert!(client.cassette().unwrap().interactions()[0].binary);
+        client.save_cassette().unwrap();
+
+        client.set_cassette(Some(Cassette::replay(&path).unwrap()));
+        let replayed = client
+            .download_imagery_frame(&frame("http://example.invalid/radar_0.png"))
+            .unwrap();
+        assert_eq!(replayed, image);
+        assert!(client.cassette().unwrap().unplayed().is_empty());
+        let error = client
+            .download_imagery_frame(&frame("http://example.invalid/radar_1.png"))
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CassetteError>(),
+            Some(&CassetteError::Unmatched {
+                request: "/radar_1.png".to_string()
+            })
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_cached_provider() {
+        use crate::provider::{CachedProvider, FakeProvider, ProviderEndpoint, WeatherProvider};
+        use std::sync::atomic::AtomicUsize;
+        let conditions = fs::read_to_string("assets/conditions_details.json").unwrap();
+        let locations = fs::read_to_string("assets/locations_search.json").unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut fake = FakeProvider::new();
+        let fake_calls = Arc::clone(&calls);
+        fake.on_current_conditions(move || {
+            fake_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(serde_json::from_str(&conditions)?)
+        });
+        let fake_calls = Arc::clone(&calls);
+        fake.on_search_locations(move |text| {
+            fake_calls.fetch_add(1, Ordering::SeqCst);
+            match text.as_str() {
+                "nowhere" => Err("no location".into()),
+                _ => Ok(serde_json::from_str(&locations)?),
+            }
+        });
+        let mut cached = CachedProvider::new(fake, Duration::from_secs(60));
+        cached.get_current_conditions().unwrap();
+        let conditions = cached.get_current_conditions().unwrap();
+        assert_eq!(conditions[0].relative_humidity, 43);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        cached.search_locations("paris").unwrap();
+        cached.search_locations("lyon").unwrap();
+        cached.search_locations("paris").unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(cached.search_locations("nowhere").is_err());
+        assert!(cached.search_locations("nowhere").is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        cached.set_ttl(ProviderEndpoint::CurrentConditions, Duration::ZERO);
+        cached.get_current_conditions().unwrap();
+        cached.get_current_conditions().unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 7);
+        cached.provider_mut();
+        cached.search_locations("paris").unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 8);
+
+        /// Transport answering conditions dated at noon, expiring at `expires`
+        #[derive(Debug)]
+        struct DatedTransport {
+            expires: &'static str,
+            requests: Arc<AtomicUsize>,
+        }
+        impl Transport for DatedTransport {
+            fn get(&self, _: &HttpRequest) -> std::result::Result<HttpResponse, TransportError> {
+                self.requests.fetch_add(1, Ordering::SeqCst);
+                Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![
+                        (
+                            "Date".to_string(),
+                            "Tue, 28 May 2019 12:00:00 GMT".to_string(),
+                        ),
+                        ("Expires".to_string(), self.expires.to_string()),
+                    ],
+                    body: fs::read("assets/conditions_details.json").unwrap(),
+                })
+            }
+        }
+        for (expires, requests) in &[
+            ("Tue, 28 May 2019 12:10:00 GMT", 1),
+            ("Tue, 28 May 2019 12:00:00 GMT", 2),
+        ] {
+            let sent = Arc::new(AtomicUsize::new(0));
+            let mut client = mock_client("abcdefg".to_string(), Some(623), None);
+            client.set_transport(DatedTransport {
+                expires,
+                requests: Arc::clone(&sent),
+            });
+            let cached = CachedProvider::new(client, Duration::from_secs(3600));
+            let response = cached.get_current_conditions_with_meta().unwrap();
+            assert_eq!(response.expires.as_deref(), Some(*expires));
+            cached.get_current_conditions().unwrap();
+            assert_eq!(sent.load(Ordering::SeqCst), *requests);
+        }
+    }
+
+    #[test]
+    fn test_weather_provider() {
+        use crate::provider::{FakeProvider, WeatherProvider};
+        fn humidity<P: WeatherProvider>(provider: &P) -> Result<i32> {
+            Ok(provider.get_current_conditions()?[0].relative_humidity)
+        }
+        let conditions = fs::read_to_string("assets/conditions_details.json").unwrap();
+        let _mock = mock("GET", "/currentconditions/v1/90037")
+            .match_query(Matcher::Any)
+            .with_body(&conditions)
+            .create();
+        let client = mock_client("abcdefg".to_string(), Some(90037), None);
+        assert_eq!(humidity(&client).unwrap(), 43);
+
+        let mut fake = FakeProvider::new();
+        assert!(humidity(&fake)
+            .unwrap_err()
+            .to_string()
+            .contains("get_current_conditions"));
+        fake.on_current_conditions(move || Ok(serde_json::from_str(&conditions)?));
+        assert_eq!(humidity(&fake).unwrap(), 43);
+        fake.on_daily_forecasts(|period| match period {
+            DailyPeriod::Days5 => Ok(serde_json::from_str(&fs::read_to_string(
+                "assets/daily5.json",
+            )?)?),
+            period => {
+                Err(
+                    AccuweatherInvalidParameterError::new("daily period", period.days(), "5")
+                        .into(),
+                )
+            }
+        });
+        let daily = fake.get_daily_forecasts(DailyPeriod::Days5).unwrap();
+        assert_eq!(daily.daily_forecasts.len(), 5);
+        assert!(fake.get_daily_forecasts(DailyPeriod::Days10).is_err());
+    }
+
+    #[test]
+    fn test_transports() {
+        let conditions = fs::read_to_string("assets/conditions_details.json").unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut client = mock_client("abcdefg".to_string(), Some(623), None);
+        client.set_base_url("http://example.invalid".to_string());
+        client.set_auth_mode(AuthMode::Header);
+        client.set_timeout(Some(Duration::from_secs(5)));
+        client.set_transport(FixedTransport {
+            status: 200,
+            body: conditions.clone(),
+            requests: Arc::clone(&requests),
+        });
+        let response = client.get_current_conditions_with_meta().unwrap();
+        assert_eq!(response.body[0].relative_humidity, 43);
+        assert_eq!(response.rate_limit_remaining, Some(42));
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request
+            .url
+            .starts_with("http://example.invalid/currentconditions/v1/623?"));
+        assert!(!request.url.contains("apikey"));
+        assert_eq!(
+            request.headers,
+            vec![("Authorization".to_string(), "Bearer abcdefg".to_string())]
+        );
+        assert_eq!(request.timeout, Some(Duration::from_secs(5)));
+
+        client.set_transport(FixedTransport {
+            status: 500,
+            body: String::new(),
+            requests: Arc::clone(&requests),
+        });
+        client.set_retries(1);
+        let error = client.get_current_conditions().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AccuweatherHttpError>().unwrap().status,
+            Some(500)
+        );
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        client.set_async_transport(FixedTransport {
+            status: 200,
+            body: conditions,
+            requests: Arc::clone(&requests),
+        });
+        let answer =
+            block_on(client.get_endpoint_async(623, schedule::Endpoint::CurrentConditions))
+                .unwrap();
+        match answer {
+            schedule::Answer::CurrentConditions(conditions) => {
+                assert_eq!(conditions[0].weather_text, "Sunny")
+            }
+            answer => panic!("unexpected answer {:?}", answer),
+        }
+        assert_eq!(requests.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_async_retries() {
+        let conditions = fs::read_to_string("assets/conditions_details.json").unwrap();
+        let mut client = mock_client("abcdefg".to_string(), Some(623), None);
+        client.set_retries(2);
+        let sleeps = Arc::new(Mutex::new(Vec::new()));
+        client.set_async_transport(TimedTransport {
+            answers: SequenceTransport::new(vec![
+                (500, Vec::new()),
+                (503, Vec::new()),
+                (200, conditions.into_bytes()),
+            ]),
+            sleeps: Arc::clone(&sleeps),
+        });
+        let conditions = block_on(client.get_current_conditions_async()).unwrap();
+        assert_eq!(conditions[0].relative_humidity, 43);
+        assert_eq!(
+            *sleeps.lock().unwrap(),
+            vec![Duration::from_millis(200), Duration::from_millis(400)]
+        );
+
+        // Without a timer, the first error is returned
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        client.set_async_transport(FixedTransport {
+            status: 503,
+            body: String::new(),
+            requests: Arc::clone(&requests),
+        });
+        let error = block_on(client.get_current_conditions_async()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AccuweatherHttpError>().unwrap().status,
+            Some(503)
+        );
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_async_endpoints() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut client = mock_client("abcdefg".to_string(), Some(623), None);
+        let transport = |asset: &str| FixedTransport {
+            status: 200,
+            body: fs::read_to_string(asset).unwrap(),
+            requests: Arc::clone(&requests),
+        };
+        client.set_async_transport(transport("assets/locations_search.json"));
+        let locations = block_on(client.search_locations_async("paris")).unwrap();
+        assert_eq!(locations[0].key, "623");
+        client.set_async_transport(transport("assets/quarters5.json"));
+        let quarters = block_on(client.get_quarter_forecasts_async(DailyPeriod::Days5)).unwrap();
+        assert!(!quarters.is_empty());
+        client.set_async_transport(transport("assets/airquality_hourly12.json"));
+        let forecasts = block_on(
+            client.get_air_quality_hourly_forecasts_async(AirQualityHourlyPeriod::Hours12),
+        )
+        .unwrap();
+        assert_eq!(forecasts.len(), 12);
+        client.set_async_transport(transport("assets/climo_actuals.json"));
+        let climo = block_on(client.get_climo_actuals_async(2019, 5, 28)).unwrap();
+        assert_eq!(climo.actuals.temperatures.maximum.value, 16.1);
+        client.set_async_transport(transport("assets/imagery480.json"));
+        let imagery = block_on(client.get_imagery_async("480x480")).unwrap();
+        let frame = &imagery.radar_frames()[0];
+        client.set_async_transport(FixedTransport {
+            status: 200,
+            body: "GIF89a".to_string(),
+            requests: Arc::clone(&requests),
+        });
+        let image = block_on(client.download_imagery_frame_async(frame)).unwrap();
+        assert_eq!(image, b"GIF89a");
+
+        let paths: Vec<String> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| Url::parse(&request.url).unwrap().path().to_string())
+            .collect();
+        assert_eq!(
+            paths[..5],
+            [
+                "/locations/v1/cities/search",
+                "/forecasts/v1/daily/5day/quarters/623",
+                "/airquality/v2/forecasts/hourly/12hour/623",
+                "/climo/v1/actuals/2019/5/28/623",
+                "/imagery/v1/maps/radsat/480x480/623",
+            ]
+        );
+        assert_eq!(paths[5], Url::parse(&frame.url).unwrap().path());
+    }
+
+    #[cfg(feature = "reqwest-blocking")]
+    #[test]
+    fn test_reqwest_transport_errors() {
+        let transport = transport::ReqwestTransport::new();
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let request = HttpRequest {
+            url: format!("http://127.0.0.1:{}/", port),
+            headers: Vec::new(),
+            timeout: Some(Duration::from_secs(5)),
+        };
+        match Transport::get(&transport, &request) {
+            Err(TransportError::Connection(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+        // An invalid request is not retried
+        let request = HttpRequest {
+            headers: vec![("bad header".to_string(), "value".to_string())],
+            ..request
+        };
+        match Transport::get(&transport, &request) {
+            Err(TransportError::Other(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[cfg(feature = "ureq")]
+    #[test]
+    fn test_ureq_transport() {
+        let _mock = mock("GET", "/currentconditions/v1/90038")
+            .match_query(Matcher::UrlEncoded("apikey".into(), "abcdefg".into()))
+            .with_body(fs::read_to_string("assets/conditions_details.json").unwrap())
+            .create();
+        let _forbidden = mock("GET", "/currentconditions/v1/90039")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .create();
+        let mut client = mock_client("abcdefg".to_string(), Some(90038), None);
+        client.set_transport(transport::UreqTransport::new());
+        let conditions = client.get_current_conditions().unwrap();
+        assert_eq!(conditions[0].relative_humidity, 43);
+        client.set_location(Some(90039));
+        let error = client.get_current_conditions().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<AccuweatherHttpError>().unwrap().status,
+            Some(403)
+        );
+    }
+
+    #[test]
+    fn test_air_quality_current_ok() {
         let _mocks = set_mocks();
         let api_key = "abcdefg".to_string();
-        let client = Accuweather::new(api_key, Some(12345), None);
-        let res_forecasts = client.get_hourly_forecasts(12);
-        let forecasts = res_forecasts.unwrap();
-        assert_eq!(forecasts[11].temperature.value, 7.2);
+        let client = mock_client(api_key, Some(12345), None);
+        let air_quality = client.get_air_quality_current_conditions().unwrap();
+        assert_eq!(air_quality.dominant_pollutant, "O3");
+        let ozone = air_quality.pollutant("O3").unwrap();
+        assert_eq!(ozone.concentration.value, 84.3);
+        assert_eq!(ozone.concentration.unit, "µg/m³");
     }
     #[test]
-    fn test_hourly_forecast_nok_forbidden() {
+    fn test_air_quality_current_nok_forbidden() {
         let _mocks = set_mocks();
         let api_key = "bad_key".to_string();
-        let client = Accuweather::new(api_key, Some(12345), None);
-        let res_forecasts = client.get_hourly_forecasts(12);
-        assert!(res_forecasts.is_err());
+        let client = mock_client(api_key, Some(12345), None);
+        assert!(client.get_air_quality_current_conditions().is_err());
     }
     #[test]
-    fn test_hourly_forecast_nok_badlocation() {
+    fn test_air_quality_hourly_ok() {
         let _mocks = set_mocks();
-        let api_key = "bad_key".to_string();
-        let client = Accuweather::new(api_key, Some(123456), None);
-        let res_forecasts = client.get_hourly_forecasts(12);
-        assert!(res_forecasts.is_err());
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let forecasts = client
+            .get_air_quality_hourly_forecasts(AirQualityHourlyPeriod::Hours12)
+            .unwrap();
+        assert_eq!(forecasts.len(), 12);
+        assert_eq!(forecasts[0].pollutants.len(), 6);
+    }
+    #[test]
+    fn test_air_quality_daily_ok() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let forecasts = client
+            .get_air_quality_daily_forecasts(AirQualityDailyPeriod::Days4)
+            .unwrap();
+        assert_eq!(forecasts.len(), 4);
+        assert!(forecasts[3].pollutant("PM2.5").is_some());
     }
 
     #[test]
-    fn test_current_condition_ok() {
+    fn test_climo_actuals_ok() {
         let _mocks = set_mocks();
         let api_key = "abcdefg".to_string();
-        let client = Accuweather::new(api_key, Some(12345), None);
-        let res_conditions = client.get_current_conditions();
-        let conditions = res_conditions.unwrap();
-        assert_eq!(conditions[0].temperature.metric.value, 27.9);
+        let client = mock_client(api_key, Some(12345), None);
+        let climo = client.get_climo_actuals(2019, 5, 28).unwrap();
+        assert_eq!(climo.actuals.temperatures.maximum.value, 16.1);
+        assert_eq!(climo.actuals.degree_days.heating.value, 7.0);
     }
     #[test]
-    fn test_current_condition_nok_forbidden() {
+    fn test_climo_records_ok() {
         let _mocks = set_mocks();
-        let api_key = "bad_key".to_string();
-        let client = Accuweather::new(api_key, Some(12345), None);
-        let res_conditions = client.get_current_conditions();
-        assert!(res_conditions.is_err());
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let climo = client.get_climo_records(2019, 5, 28).unwrap();
+        assert_eq!(climo.records.temperatures.maximum.measurement.value, 31.4);
+        assert_eq!(climo.records.temperatures.maximum.year, 2005);
     }
     #[test]
-    fn test_current_condition_nok_badlocation() {
+    fn test_climo_month_summary_ok() {
         let _mocks = set_mocks();
-        let api_key = "bad_key".to_string();
-        let client = Accuweather::new(api_key, Some(123456), None);
-        let res_conditions = client.get_current_conditions();
-        assert!(res_conditions.is_err());
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let summary = client.get_climo_month_summary(2019, 5).unwrap();
+        assert_eq!(summary.actuals.len(), 31);
+        assert_eq!(summary.normals.len(), 31);
+        assert_eq!(summary.records.len(), 31);
+    }
+    #[test]
+    fn test_climo_nok_bad_date() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        assert!(client.get_climo_normals(2019, 5, 32).is_err());
+        assert!(client.get_climo_records(2019, 0, 28).is_err());
+        assert!(client.get_climo_actuals(2019, 4, 31).is_err());
+        assert!(client.get_climo_actuals(2019, 2, 29).is_err());
+        assert!(client.get_climo_actuals(1900, 2, 29).is_err());
+        assert!(client
+            .climo_day_query(12345, "actuals", 2020, 2, 29)
+            .is_ok());
+        assert!(client
+            .climo_day_query(12345, "actuals", 2000, 2, 29)
+            .is_ok());
+        assert!(client
+            .climo_day_query(12345, "actuals", 2019, 12, 31)
+            .is_ok());
+    }
+    #[test]
+    fn test_climo_anomalies() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let forecasts = client.get_daily_forecasts(DailyPeriod::Days5).unwrap();
+        let normals = client.get_climo_normals(2019, 5, 28).unwrap();
+        let anomalies = forecasts.daily_forecasts[0].anomalies(&normals.normals);
+        assert!((anomalies.maximum - (15.3 - 19.0)).abs() < 1e-4);
+        assert!((anomalies.minimum - (5.4 - 9.1)).abs() < 1e-4);
+        assert!((anomalies.precipitation - (2.0 - 2.1)).abs() < 1e-4);
+        assert!((anomalies.heating_degree_days - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_imagery_ok() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let imagery = client.get_imagery("480x480").unwrap();
+        assert_eq!(imagery.radar.images.len(), 8);
+        let frames = client.get_satellite_frames("480x480").unwrap();
+        assert_eq!(frames.len(), 6);
+        assert_eq!(frames[0].kind, ImageryType::Satellite);
+        assert_eq!(frames[0].size, "480x480");
+        assert_eq!(frames[0].date, "2019-08-31T12:00:00+00:00");
+    }
+    #[test]
+    fn test_imagery_frames_oldest_first() {
+        let mut imagery: Value =
+            serde_json::from_str(&fs::read_to_string("assets/imagery480.json").unwrap()).unwrap();
+        for layer in &["Radar", "Satellite"] {
+            let images = imagery[layer]["Images"].as_array_mut().unwrap();
+            images.reverse();
+            images.swap(1, 3);
+        }
+        let _mock = mock("GET", "/imagery/v1/maps/radsat/480x480/90040")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(imagery.to_string())
+            .create();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(90040), None);
+        let radar = client.get_radar_frames("480x480").unwrap();
+        assert_eq!(radar.len(), 8);
+        assert_eq!(radar[0].date, "2019-08-31T12:30:00+00:00");
+        assert!(radar.windows(2).all(|pair| pair[0].date < pair[1].date));
+        let satellite = client.get_satellite_frames("480x480").unwrap();
+        assert_eq!(satellite[0].date, "2019-08-31T12:00:00+00:00");
+        assert!(satellite.windows(2).all(|pair| pair[0].date < pair[1].date));
+    }
+    #[test]
+    fn test_imagery_nok_bad_resolution() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        assert!(client.get_radar_frames("480").is_err());
+    }
+    #[test]
+    fn test_download_imagery_frame_ok() {
+        let _mocks = set_mocks();
+        let api_key = "abcdefg".to_string();
+        let client = mock_client(api_key, Some(12345), None);
+        let frame = ImageryFrame {
+            kind: ImageryType::Radar,
+            url: format!("{}/images/radar_0.png", mockito::server_url()),
+            date: "2019-08-31T12:30:00+00:00".to_string(),
+            size: "480x480".to_string(),
+        };
+        let image = client.download_imagery_frame(&frame).unwrap();
+        assert_eq!(image, vec![0x89, b'P', b'N', b'G']);
     }
 }
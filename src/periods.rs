@@ -0,0 +1,204 @@
+//! Forecast periods and the subscription tiers allowing them
+use crate::AccuweatherInvalidParameterError;
+use std::convert::TryFrom;
+
+/// Length of an hourly forecast
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HourlyPeriod {
+    Hour1,
+    Hours12,
+    Hours24,
+    Hours72,
+    Hours120,
+    Hours240,
+}
+
+impl HourlyPeriod {
+    /// Number of forecast hours
+    pub fn hours(self) -> u16 {
+        match self {
+            HourlyPeriod::Hour1 => 1,
+            HourlyPeriod::Hours12 => 12,
+            HourlyPeriod::Hours24 => 24,
+            HourlyPeriod::Hours72 => 72,
+            HourlyPeriod::Hours120 => 120,
+            HourlyPeriod::Hours240 => 240,
+        }
+    }
+}
+
+impl TryFrom<u16> for HourlyPeriod {
+    type Error = AccuweatherInvalidParameterError;
+
+    fn try_from(hours: u16) -> Result<Self, Self::Error> {
+        match hours {
+            1 => Ok(HourlyPeriod::Hour1),
+            12 => Ok(HourlyPeriod::Hours12),
+            24 => Ok(HourlyPeriod::Hours24),
+            72 => Ok(HourlyPeriod::Hours72),
+            120 => Ok(HourlyPeriod::Hours120),
+            240 => Ok(HourlyPeriod::Hours240),
+            _ => Err(AccuweatherInvalidParameterError::new(
+                "hourly period",
+                hours,
+                "1, 12, 24, 72, 120 or 240 hours",
+            )),
+        }
+    }
+}
+
+/// Length of a daily forecast
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DailyPeriod {
+    Day1,
+    Days5,
+    Days10,
+    Days15,
+    Days25,
+    Days45,
+}
+
+impl DailyPeriod {
+    /// Number of forecast days
+    pub fn days(self) -> u16 {
+        match self {
+            DailyPeriod::Day1 => 1,
+            DailyPeriod::Days5 => 5,
+            DailyPeriod::Days10 => 10,
+            DailyPeriod::Days15 => 15,
+            DailyPeriod::Days25 => 25,
+            DailyPeriod::Days45 => 45,
+        }
+    }
+}
+
+impl TryFrom<u16> for DailyPeriod {
+    type Error = AccuweatherInvalidParameterError;
+
+    fn try_from(days: u16) -> Result<Self, Self::Error> {
+        match days {
+            1 => Ok(DailyPeriod::Day1),
+            5 => Ok(DailyPeriod::Days5),
+            10 => Ok(DailyPeriod::Days10),
+            15 => Ok(DailyPeriod::Days15),
+            25 => Ok(DailyPeriod::Days25),
+            45 => Ok(DailyPeriod::Days45),
+            _ => Err(AccuweatherInvalidParameterError::new(
+                "daily period",
+                days,
+                "1, 5, 10, 15, 25 or 45 days",
+            )),
+        }
+    }
+}
+
+/// Length of an hourly air quality forecast
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AirQualityHourlyPeriod {
+    Hour1,
+    Hours12,
+    Hours24,
+    Hours48,
+    Hours72,
+    Hours96,
+}
+
+impl AirQualityHourlyPeriod {
+    /// Number of forecast hours
+    pub fn hours(self) -> u16 {
+        match self {
+            AirQualityHourlyPeriod::Hour1 => 1,
+            AirQualityHourlyPeriod::Hours12 => 12,
+            AirQualityHourlyPeriod::Hours24 => 24,
+            AirQualityHourlyPeriod::Hours48 => 48,
+            AirQualityHourlyPeriod::Hours72 => 72,
+            AirQualityHourlyPeriod::Hours96 => 96,
+        }
+    }
+}
+
+impl TryFrom<u16> for AirQualityHourlyPeriod {
+    type Error = AccuweatherInvalidParameterError;
+
+    fn try_from(hours: u16) -> Result<Self, Self::Error> {
+        match hours {
+            1 => Ok(AirQualityHourlyPeriod::Hour1),
+            12 => Ok(AirQualityHourlyPeriod::Hours12),
+            24 => Ok(AirQualityHourlyPeriod::Hours24),
+            48 => Ok(AirQualityHourlyPeriod::Hours48),
+            72 => Ok(AirQualityHourlyPeriod::Hours72),
+            96 => Ok(AirQualityHourlyPeriod::Hours96),
+            _ => Err(AccuweatherInvalidParameterError::new(
+                "air quality hourly period",
+                hours,
+                "1, 12, 24, 48, 72 or 96 hours",
+            )),
+        }
+    }
+}
+
+/// Length of a daily air quality forecast
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AirQualityDailyPeriod {
+    Day1,
+    Days4,
+}
+
+impl AirQualityDailyPeriod {
+    /// Number of forecast days
+    pub fn days(self) -> u16 {
+        match self {
+            AirQualityDailyPeriod::Day1 => 1,
+            AirQualityDailyPeriod::Days4 => 4,
+        }
+    }
+}
+
+impl TryFrom<u16> for AirQualityDailyPeriod {
+    type Error = AccuweatherInvalidParameterError;
+
+    fn try_from(days: u16) -> Result<Self, Self::Error> {
+        match days {
+            1 => Ok(AirQualityDailyPeriod::Day1),
+            4 => Ok(AirQualityDailyPeriod::Days4),
+            _ => Err(AccuweatherInvalidParameterError::new(
+                "air quality daily period",
+                days,
+                "1 or 4 days",
+            )),
+        }
+    }
+}
+
+/// Accuweather subscription tier, which bounds the forecast periods a key may request
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionTier {
+    /// Free trial keys: 1 and 12 hours, 1 and 5 days
+    Limited,
+    /// Up to 120 hours and 15 days
+    #[default]
+    Standard,
+    /// Every period, including 240 hours, 25 and 45 days
+    Enterprise,
+}
+
+impl SubscriptionTier {
+    /// Whether the tier allows to request an hourly forecast of this period
+    pub fn allows_hourly(self, period: HourlyPeriod) -> bool {
+        match self {
+            SubscriptionTier::Limited => period <= HourlyPeriod::Hours12,
+            SubscriptionTier::Standard => period <= HourlyPeriod::Hours120,
+            SubscriptionTier::Enterprise => true,
+        }
+    }
+
+    /// Whether the tier allows to request a daily forecast of this period
+    pub fn allows_daily(self, period: DailyPeriod) -> bool {
+        match self {
+            SubscriptionTier::Limited => period <= DailyPeriod::Days5,
+            SubscriptionTier::Standard => period <= DailyPeriod::Days15,
+            SubscriptionTier::Enterprise => true,
+        }
+    }
+}
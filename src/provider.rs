@@ -0,0 +1,912 @@
+//! Abstraction of the weather endpoints
+//!
+//! `WeatherProvider` covers the endpoints of the client, so that application code can be
+//! generic over where the answers come from. It is implemented by `Accuweather`, by
+//! `CachedProvider` keeping the answers of another provider until they are stale, and by
+//! `FakeProvider` whose answers are programmed with closures, for unit tests without http.
+//!
+//! # Example
+//! ```
+//! use accuweather::provider::{FakeProvider, WeatherProvider};
+//!
+//! fn weather_text<P: WeatherProvider>(provider: &P) -> String {
+//!     match provider.get_current_conditions() {
+//!         Ok(conditions) => conditions[0].weather_text.clone(),
+//!         Err(error) => error.to_string(),
+//!     }
+//! }
+//!
+//! let mut fake = FakeProvider::new();
+//! fake.on_current_conditions(|| Err("offline".into()));
+//! assert_eq!(weather_text(&fake), "offline");
+//! ```
+use crate::periods::{AirQualityDailyPeriod, AirQualityHourlyPeriod, DailyPeriod, HourlyPeriod};
+use crate::response::ApiResponse;
+use crate::types::*;
+use crate::{Accuweather, Result};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Source of weather answers: location searches, and the endpoints of the location it is
+/// set to
+///
+/// The `_with_meta` methods answer without http metadata by default, for providers not
+/// sending requests.
+pub trait WeatherProvider {
+    /// See `Accuweather::search_locations`
+    fn search_locations(&self, text: &str) -> Result<Vec<Location>>;
+
+    /// See `Accuweather::get_hourly_forecasts`
+    fn get_hourly_forecasts(&self, period: HourlyPeriod) -> Result<Vec<HourlyForecast>>;
+
+    /// See `Accuweather::get_daily_forecasts`
+    fn get_daily_forecasts(&self, period: DailyPeriod) -> Result<DailyForecastsAnswer>;
+
+    /// See `Accuweather::get_quarter_forecasts`
+    fn get_quarter_forecasts(&self, period: DailyPeriod) -> Result<Vec<QuarterForecast>>;
+
+    /// See `Accuweather::get_current_conditions`
+    fn get_current_conditions(&self) -> Result<Vec<CurrentCondition>>;
+
+    /// See `Accuweather::get_air_quality_current_conditions`
+    fn get_air_quality_current_conditions(&self) -> Result<AirQuality>;
+
+    /// See `Accuweather::get_air_quality_hourly_forecasts`
+    fn get_air_quality_hourly_forecasts(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<Vec<AirQuality>>;
+
+    /// See `Accuweather::get_air_quality_daily_forecasts`
+    fn get_air_quality_daily_forecasts(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<Vec<AirQuality>>;
+
+    /// See `Accuweather::get_climo_actuals`
+    fn get_climo_actuals(&self, year: i32, month: u8, day: u8) -> Result<ClimoActualsAnswer>;
+
+    /// See `Accuweather::get_climo_normals`
+    fn get_climo_normals(&self, year: i32, month: u8, day: u8) -> Result<ClimoNormalsAnswer>;
+
+    /// See `Accuweather::get_climo_records`
+    fn get_climo_records(&self, year: i32, month: u8, day: u8) -> Result<ClimoRecordsAnswer>;
+
+    /// See `Accuweather::get_climo_month_summary`
+    fn get_climo_month_summary(&self, year: i32, month: u8) -> Result<ClimoSummaryAnswer>;
+
+    /// See `Accuweather::get_imagery`
+    fn get_imagery(&self, resolution: &str) -> Result<ImageryAnswer>;
+
+    /// See `Accuweather::search_locations_with_meta`
+    fn search_locations_with_meta(&self, text: &str) -> Result<ApiResponse<Vec<Location>>> {
+        self.search_locations(text).map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_hourly_forecasts_with_meta`
+    fn get_hourly_forecasts_with_meta(
+        &self,
+        period: HourlyPeriod,
+    ) -> Result<ApiResponse<Vec<HourlyForecast>>> {
+        self.get_hourly_forecasts(period)
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_daily_forecasts_with_meta`
+    fn get_daily_forecasts_with_meta(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<ApiResponse<DailyForecastsAnswer>> {
+        self.get_daily_forecasts(period).map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_quarter_forecasts_with_meta`
+    fn get_quarter_forecasts_with_meta(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<ApiResponse<Vec<QuarterForecast>>> {
+        self.get_quarter_forecasts(period)
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_current_conditions_with_meta`
+    fn get_current_conditions_with_meta(&self) -> Result<ApiResponse<Vec<CurrentCondition>>> {
+        self.get_current_conditions().map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_air_quality_current_conditions_with_meta`
+    fn get_air_quality_current_conditions_with_meta(&self) -> Result<ApiResponse<AirQuality>> {
+        self.get_air_quality_current_conditions()
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_air_quality_hourly_forecasts_with_meta`
+    fn get_air_quality_hourly_forecasts_with_meta(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<ApiResponse<Vec<AirQuality>>> {
+        self.get_air_quality_hourly_forecasts(period)
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_air_quality_daily_forecasts_with_meta`
+    fn get_air_quality_daily_forecasts_with_meta(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<ApiResponse<Vec<AirQuality>>> {
+        self.get_air_quality_daily_forecasts(period)
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_climo_actuals_with_meta`
+    fn get_climo_actuals_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoActualsAnswer>> {
+        self.get_climo_actuals(year, month, day)
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_climo_normals_with_meta`
+    fn get_climo_normals_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoNormalsAnswer>> {
+        self.get_climo_normals(year, month, day)
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_climo_records_with_meta`
+    fn get_climo_records_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoRecordsAnswer>> {
+        self.get_climo_records(year, month, day)
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_climo_month_summary_with_meta`
+    fn get_climo_month_summary_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+    ) -> Result<ApiResponse<ClimoSummaryAnswer>> {
+        self.get_climo_month_summary(year, month)
+            .map(ApiResponse::from_body)
+    }
+
+    /// See `Accuweather::get_imagery_with_meta`
+    fn get_imagery_with_meta(&self, resolution: &str) -> Result<ApiResponse<ImageryAnswer>> {
+        self.get_imagery(resolution).map(ApiResponse::from_body)
+    }
+}
+
+impl WeatherProvider for Accuweather {
+    fn search_locations(&self, text: &str) -> Result<Vec<Location>> {
+        Accuweather::search_locations(self, text)
+    }
+
+    fn get_hourly_forecasts(&self, period: HourlyPeriod) -> Result<Vec<HourlyForecast>> {
+        Accuweather::get_hourly_forecasts(self, period)
+    }
+
+    fn get_daily_forecasts(&self, period: DailyPeriod) -> Result<DailyForecastsAnswer> {
+        Accuweather::get_daily_forecasts(self, period)
+    }
+
+    fn get_quarter_forecasts(&self, period: DailyPeriod) -> Result<Vec<QuarterForecast>> {
+        Accuweather::get_quarter_forecasts(self, period)
+    }
+
+    fn get_current_conditions(&self) -> Result<Vec<CurrentCondition>> {
+        Accuweather::get_current_conditions(self)
+    }
+
+    fn get_air_quality_current_conditions(&self) -> Result<AirQuality> {
+        Accuweather::get_air_quality_current_conditions(self)
+    }
+
+    fn get_air_quality_hourly_forecasts(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<Vec<AirQuality>> {
+        Accuweather::get_air_quality_hourly_forecasts(self, period)
+    }
+
+    fn get_air_quality_daily_forecasts(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<Vec<AirQuality>> {
+        Accuweather::get_air_quality_daily_forecasts(self, period)
+    }
+
+    fn get_climo_actuals(&self, year: i32, month: u8, day: u8) -> Result<ClimoActualsAnswer> {
+        Accuweather::get_climo_actuals(self, year, month, day)
+    }
+
+    fn get_climo_normals(&self, year: i32, month: u8, day: u8) -> Result<ClimoNormalsAnswer> {
+        Accuweather::get_climo_normals(self, year, month, day)
+    }
+
+    fn get_climo_records(&self, year: i32, month: u8, day: u8) -> Result<ClimoRecordsAnswer> {
+        Accuweather::get_climo_records(self, year, month, day)
+    }
+
+    fn get_climo_month_summary(&self, year: i32, month: u8) -> Result<ClimoSummaryAnswer> {
+        Accuweather::get_climo_month_summary(self, year, month)
+    }
+
+    fn get_imagery(&self, resolution: &str) -> Result<ImageryAnswer> {
+        Accuweather::get_imagery(self, resolution)
+    }
+
+    fn search_locations_with_meta(&self, text: &str) -> Result<ApiResponse<Vec<Location>>> {
+        Accuweather::search_locations_with_meta(self, text)
+    }
+
+    fn get_hourly_forecasts_with_meta(
+        &self,
+        period: HourlyPeriod,
+    ) -> Result<ApiResponse<Vec<HourlyForecast>>> {
+        Accuweather::get_hourly_forecasts_with_meta(self, period)
+    }
+
+    fn get_daily_forecasts_with_meta(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<ApiResponse<DailyForecastsAnswer>> {
+        Accuweather::get_daily_forecasts_with_meta(self, period)
+    }
+
+    fn get_quarter_forecasts_with_meta(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<ApiResponse<Vec<QuarterForecast>>> {
+        Accuweather::get_quarter_forecasts_with_meta(self, period)
+    }
+
+    fn get_current_conditions_with_meta(&self) -> Result<ApiResponse<Vec<CurrentCondition>>> {
+        Accuweather::get_current_conditions_with_meta(self)
+    }
+
+    fn get_air_quality_current_conditions_with_meta(&self) -> Result<ApiResponse<AirQuality>> {
+        Accuweather::get_air_quality_current_conditions_with_meta(self)
+    }
+
+    fn get_air_quality_hourly_forecasts_with_meta(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<ApiResponse<Vec<AirQuality>>> {
+        Accuweather::get_air_quality_hourly_forecasts_with_meta(self, period)
+    }
+
+    fn get_air_quality_daily_forecasts_with_meta(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<ApiResponse<Vec<AirQuality>>> {
+        Accuweather::get_air_quality_daily_forecasts_with_meta(self, period)
+    }
+
+    fn get_climo_actuals_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoActualsAnswer>> {
+        Accuweather::get_climo_actuals_with_meta(self, year, month, day)
+    }
+
+    fn get_climo_normals_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoNormalsAnswer>> {
+        Accuweather::get_climo_normals_with_meta(self, year, month, day)
+    }
+
+    fn get_climo_records_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoRecordsAnswer>> {
+        Accuweather::get_climo_records_with_meta(self, year, month, day)
+    }
+
+    fn get_climo_month_summary_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+    ) -> Result<ApiResponse<ClimoSummaryAnswer>> {
+        Accuweather::get_climo_month_summary_with_meta(self, year, month)
+    }
+
+    fn get_imagery_with_meta(&self, resolution: &str) -> Result<ApiResponse<ImageryAnswer>> {
+        Accuweather::get_imagery_with_meta(self, resolution)
+    }
+}
+
+/// Programmed answer of an endpoint, from its parameters
+type Handler<A, T> = Option<Box<dyn Fn(A) -> Result<T> + Send + Sync>>;
+
+/// In-memory provider answering with programmed closures
+///
+/// Endpoints not programmed fail with an error naming them.
+#[derive(Default)]
+pub struct FakeProvider {
+    search_locations: Handler<String, Vec<Location>>,
+    hourly_forecasts: Handler<HourlyPeriod, Vec<HourlyForecast>>,
+    daily_forecasts: Handler<DailyPeriod, DailyForecastsAnswer>,
+    quarter_forecasts: Handler<DailyPeriod, Vec<QuarterForecast>>,
+    current_conditions: Handler<(), Vec<CurrentCondition>>,
+    air_quality_current_conditions: Handler<(), AirQuality>,
+    air_quality_hourly_forecasts: Handler<AirQualityHourlyPeriod, Vec<AirQuality>>,
+    air_quality_daily_forecasts: Handler<AirQualityDailyPeriod, Vec<AirQuality>>,
+    climo_actuals: Handler<(i32, u8, u8), ClimoActualsAnswer>,
+    climo_normals: Handler<(i32, u8, u8), ClimoNormalsAnswer>,
+    climo_records: Handler<(i32, u8, u8), ClimoRecordsAnswer>,
+    climo_month_summary: Handler<(i32, u8), ClimoSummaryAnswer>,
+    imagery: Handler<String, ImageryAnswer>,
+}
+
+impl FakeProvider {
+    pub fn new() -> Self {
+        FakeProvider::default()
+    }
+
+    /// Answer location searches with `f` of the searched text
+    pub fn on_search_locations<F>(&mut self, f: F)
+    where
+        F: Fn(String) -> Result<Vec<Location>> + Send + Sync + 'static,
+    {
+        self.search_locations = Some(Box::new(f));
+    }
+
+    /// Answer hourly forecasts with `f` of the period
+    pub fn on_hourly_forecasts<F>(&mut self, f: F)
+    where
+        F: Fn(HourlyPeriod) -> Result<Vec<HourlyForecast>> + Send + Sync + 'static,
+    {
+        self.hourly_forecasts = Some(Box::new(f));
+    }
+
+    /// Answer daily forecasts with `f` of the period
+    pub fn on_daily_forecasts<F>(&mut self, f: F)
+    where
+        F: Fn(DailyPeriod) -> Result<DailyForecastsAnswer> + Send + Sync + 'static,
+    {
+        self.daily_forecasts = Some(Box::new(f));
+    }
+
+    /// Answer quarter forecasts with `f` of the period
+    pub fn on_quarter_forecasts<F>(&mut self, f: F)
+    where
+        F: Fn(DailyPeriod) -> Result<Vec<QuarterForecast>> + Send + Sync + 'static,
+    {
+        self.quarter_forecasts = Some(Box::new(f));
+    }
+
+    /// Answer current conditions with `f`
+    pub fn on_current_conditions<F>(&mut self, f: F)
+    where
+        F: Fn() -> Result<Vec<CurrentCondition>> + Send + Sync + 'static,
+    {
+        self.current_conditions = Some(Box::new(move |()| f()));
+    }
+
+    /// Answer air quality current conditions with `f`
+    pub fn on_air_quality_current_conditions<F>(&mut self, f: F)
+    where
+        F: Fn() -> Result<AirQuality> + Send + Sync + 'static,
+    {
+        self.air_quality_current_conditions = Some(Box::new(move |()| f()));
+    }
+
+    /// Answer air quality hourly forecasts with `f` of the period
+    pub fn on_air_quality_hourly_forecasts<F>(&mut self, f: F)
+    where
+        F: Fn(AirQualityHourlyPeriod) -> Result<Vec<AirQuality>> + Send + Sync + 'static,
+    {
+        self.air_quality_hourly_forecasts = Some(Box::new(f));
+    }
+
+    /// Answer air quality daily forecasts with `f` of the period
+    pub fn on_air_quality_daily_forecasts<F>(&mut self, f: F)
+    where
+        F: Fn(AirQualityDailyPeriod) -> Result<Vec<AirQuality>> + Send + Sync + 'static,
+    {
+        self.air_quality_daily_forecasts = Some(Box::new(f));
+    }
+
+    /// Answer climatology actuals with `f` of the year, month and day
+    pub fn on_climo_actuals<F>(&mut self, f: F)
+    where
+        F: Fn(i32, u8, u8) -> Result<ClimoActualsAnswer> + Send + Sync + 'static,
+    {
+        self.climo_actuals = Some(Box::new(move |(year, month, day)| f(year, month, day)));
+    }
+
+    /// Answer climatology normals with `f` of the year, month and day
+    pub fn on_climo_normals<F>(&mut self, f: F)
+    where
+        F: Fn(i32, u8, u8) -> Result<ClimoNormalsAnswer> + Send + Sync + 'static,
+    {
+        self.climo_normals = Some(Box::new(move |(year, month, day)| f(year, month, day)));
+    }
+
+    /// Answer climatology records with `f` of the year, month and day
+    pub fn on_climo_records<F>(&mut self, f: F)
+    where
+        F: Fn(i32, u8, u8) -> Result<ClimoRecordsAnswer> + Send + Sync + 'static,
+    {
+        self.climo_records = Some(Box::new(move |(year, month, day)| f(year, month, day)));
+    }
+
+    /// Answer climatology month summaries with `f` of the year and month
+    pub fn on_climo_month_summary<F>(&mut self, f: F)
+    where
+        F: Fn(i32, u8) -> Result<ClimoSummaryAnswer> + Send + Sync + 'static,
+    {
+        self.climo_month_summary = Some(Box::new(move |(year, month)| f(year, month)));
+    }
+
+    /// Answer imagery with `f` of the resolution
+    pub fn on_imagery<F>(&mut self, f: F)
+    where
+        F: Fn(String) -> Result<ImageryAnswer> + Send + Sync + 'static,
+    {
+        self.imagery = Some(Box::new(f));
+    }
+}
+
+impl fmt::Debug for FakeProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FakeProvider").finish_non_exhaustive()
+    }
+}
+
+/// Call a programmed answer, failing when the endpoint is not programmed
+fn answer<A, T>(handler: &Handler<A, T>, endpoint: &str, parameters: A) -> Result<T> {
+    match handler {
+        Some(f) => f(parameters),
+        None => Err(format!("{} is not programmed in the fake provider", endpoint).into()),
+    }
+}
+
+impl WeatherProvider for FakeProvider {
+    fn search_locations(&self, text: &str) -> Result<Vec<Location>> {
+        answer(&self.search_locations, "search_locations", text.to_string())
+    }
+
+    fn get_hourly_forecasts(&self, period: HourlyPeriod) -> Result<Vec<HourlyForecast>> {
+        answer(&self.hourly_forecasts, "get_hourly_forecasts", period)
+    }
+
+    fn get_daily_forecasts(&self, period: DailyPeriod) -> Result<DailyForecastsAnswer> {
+        answer(&self.daily_forecasts, "get_daily_forecasts", period)
+    }
+
+    fn get_quarter_forecasts(&self, period: DailyPeriod) -> Result<Vec<QuarterForecast>> {
+        answer(&self.quarter_forecasts, "get_quarter_forecasts", period)
+    }
+
+    fn get_current_conditions(&self) -> Result<Vec<CurrentCondition>> {
+        answer(&self.current_conditions, "get_current_conditions", ())
+    }
+
+    fn get_air_quality_current_conditions(&self) -> Result<AirQuality> {
+        answer(
+            &self.air_quality_current_conditions,
+            "get_air_quality_current_conditions",
+            (),
+        )
+    }
+
+    fn get_air_quality_hourly_forecasts(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<Vec<AirQuality>> {
+        answer(
+            &self.air_quality_hourly_forecasts,
+            "get_air_quality_hourly_forecasts",
+            period,
+        )
+    }
+
+    fn get_air_quality_daily_forecasts(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<Vec<AirQuality>> {
+        answer(
+            &self.air_quality_daily_forecasts,
+            "get_air_quality_daily_forecasts",
+            period,
+        )
+    }
+
+    fn get_climo_actuals(&self, year: i32, month: u8, day: u8) -> Result<ClimoActualsAnswer> {
+        answer(&self.climo_actuals, "get_climo_actuals", (year, month, day))
+    }
+
+    fn get_climo_normals(&self, year: i32, month: u8, day: u8) -> Result<ClimoNormalsAnswer> {
+        answer(&self.climo_normals, "get_climo_normals", (year, month, day))
+    }
+
+    fn get_climo_records(&self, year: i32, month: u8, day: u8) -> Result<ClimoRecordsAnswer> {
+        answer(&self.climo_records, "get_climo_records", (year, month, day))
+    }
+
+    fn get_climo_month_summary(&self, year: i32, month: u8) -> Result<ClimoSummaryAnswer> {
+        answer(
+            &self.climo_month_summary,
+            "get_climo_month_summary",
+            (year, month),
+        )
+    }
+
+    fn get_imagery(&self, resolution: &str) -> Result<ImageryAnswer> {
+        answer(&self.imagery, "get_imagery", resolution.to_string())
+    }
+}
+
+/// An endpoint of `WeatherProvider`, to set its time to live in a `CachedProvider`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProviderEndpoint {
+    SearchLocations,
+    HourlyForecasts,
+    DailyForecasts,
+    QuarterForecasts,
+    CurrentConditions,
+    AirQualityCurrentConditions,
+    AirQualityHourlyForecasts,
+    AirQualityDailyForecasts,
+    ClimoActuals,
+    ClimoNormals,
+    ClimoRecords,
+    ClimoMonthSummary,
+    Imagery,
+}
+
+/// A cached answer
+struct Entry {
+    /// the `ApiResponse` of the answer
+    response: Box<dyn Any + Send>,
+    stale_at: Instant,
+}
+
+/// Provider keeping the answers of another one until they are stale
+///
+/// Answers are kept for each endpoint and parameters, for the time to live of the endpoint
+/// and at most until their `Expires` header. Errors are not kept.
+pub struct CachedProvider<P> {
+    provider: P,
+    ttl: Duration,
+    ttls: HashMap<ProviderEndpoint, Duration>,
+    entries: Mutex<HashMap<(ProviderEndpoint, String), Entry>>,
+}
+
+impl<P: WeatherProvider> CachedProvider<P> {
+    /// Cache the answers of `provider` for `ttl` at most
+    pub fn new(provider: P, ttl: Duration) -> Self {
+        CachedProvider {
+            provider,
+            ttl,
+            ttls: HashMap::new(),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the time to live of the answers of an endpoint, instead of the one given to `new`
+    ///
+    /// A zero duration disables the cache of the endpoint. Its cached answers are dropped.
+    pub fn set_ttl(&mut self, endpoint: ProviderEndpoint, ttl: Duration) {
+        self.ttls.insert(endpoint, ttl);
+        self.entries
+            .get_mut()
+            .unwrap()
+            .retain(|(cached, _), _| *cached != endpoint);
+    }
+
+    /// The cached provider
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// The cached provider, to change its settings, such as the location of a client
+    ///
+    /// The cached answers are dropped, as they may not match the new settings.
+    pub fn provider_mut(&mut self) -> &mut P {
+        self.clear();
+        &mut self.provider
+    }
+
+    /// Drop the cached answers
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Answer from the cache when fresh, else from `fetch`, caching its answer
+    fn cached<T, F>(
+        &self,
+        endpoint: ProviderEndpoint,
+        parameters: String,
+        fetch: F,
+    ) -> Result<ApiResponse<T>>
+    where
+        T: Clone + Send + 'static,
+        F: FnOnce() -> Result<ApiResponse<T>>,
+    {
+        let key = (endpoint, parameters);
+        if let Some(entry) = self.entries.lock().unwrap().get(&key) {
+            if entry.stale_at > Instant::now() {
+                if let Some(response) = entry.response.downcast_ref::<ApiResponse<T>>() {
+                    return Ok(response.clone());
+                }
+            }
+        }
+        let response = fetch()?;
+        let mut ttl = self.ttls.get(&endpoint).cloned().unwrap_or(self.ttl);
+        if let Some(expires) = freshness(&response) {
+            ttl = ttl.min(expires);
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if ttl > Duration::ZERO {
+            let entry = Entry {
+                response: Box::new(response.clone()),
+                stale_at: Instant::now() + ttl,
+            };
+            entries.insert(key, entry);
+        } else {
+            entries.remove(&key);
+        }
+        Ok(response)
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for CachedProvider<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedProvider")
+            .field("provider", &self.provider)
+            .field("ttl", &self.ttl)
+            .field("ttls", &self.ttls)
+            .field("entries", &self.entries.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// Time an answer stays fresh according to its `Expires` header, from its `Date` header or
+/// from now without it
+fn freshness<T>(response: &ApiResponse<T>) -> Option<Duration> {
+    let expires = httpdate::parse_http_date(response.expires.as_ref()?).ok()?;
+    let date = response
+        .date
+        .as_ref()
+        .and_then(|date| httpdate::parse_http_date(date).ok())
+        .unwrap_or_else(SystemTime::now);
+    Some(expires.duration_since(date).unwrap_or(Duration::ZERO))
+}
+
+impl<P: WeatherProvider> WeatherProvider for CachedProvider<P> {
+    fn search_locations(&self, text: &str) -> Result<Vec<Location>> {
+        self.search_locations_with_meta(text)
+            .map(|response| response.body)
+    }
+
+    fn search_locations_with_meta(&self, text: &str) -> Result<ApiResponse<Vec<Location>>> {
+        self.cached(
+            ProviderEndpoint::SearchLocations,
+            format!("{:?}", text),
+            || self.provider.search_locations_with_meta(text),
+        )
+    }
+
+    fn get_hourly_forecasts(&self, period: HourlyPeriod) -> Result<Vec<HourlyForecast>> {
+        self.get_hourly_forecasts_with_meta(period)
+            .map(|response| response.body)
+    }
+
+    fn get_hourly_forecasts_with_meta(
+        &self,
+        period: HourlyPeriod,
+    ) -> Result<ApiResponse<Vec<HourlyForecast>>> {
+        self.cached(
+            ProviderEndpoint::HourlyForecasts,
+            format!("{:?}", period),
+            || self.provider.get_hourly_forecasts_with_meta(period),
+        )
+    }
+
+    fn get_daily_forecasts(&self, period: DailyPeriod) -> Result<DailyForecastsAnswer> {
+        self.get_daily_forecasts_with_meta(period)
+            .map(|response| response.body)
+    }
+
+    fn get_daily_forecasts_with_meta(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<ApiResponse<DailyForecastsAnswer>> {
+        self.cached(
+            ProviderEndpoint::DailyForecasts,
+            format!("{:?}", period),
+            || self.provider.get_daily_forecasts_with_meta(period),
+        )
+    }
+
+    fn get_quarter_forecasts(&self, period: DailyPeriod) -> Result<Vec<QuarterForecast>> {
+        self.get_quarter_forecasts_with_meta(period)
+            .map(|response| response.body)
+    }
+
+    fn get_quarter_forecasts_with_meta(
+        &self,
+        period: DailyPeriod,
+    ) -> Result<ApiResponse<Vec<QuarterForecast>>> {
+        self.cached(
+            ProviderEndpoint::QuarterForecasts,
+            format!("{:?}", period),
+            || self.provider.get_quarter_forecasts_with_meta(period),
+        )
+    }
+
+    fn get_current_conditions(&self) -> Result<Vec<CurrentCondition>> {
+        self.get_current_conditions_with_meta()
+            .map(|response| response.body)
+    }
+
+    fn get_current_conditions_with_meta(&self) -> Result<ApiResponse<Vec<CurrentCondition>>> {
+        self.cached(ProviderEndpoint::CurrentConditions, String::new(), || {
+            self.provider.get_current_conditions_with_meta()
+        })
+    }
+
+    fn get_air_quality_current_conditions(&self) -> Result<AirQuality> {
+        self.get_air_quality_current_conditions_with_meta()
+            .map(|response| response.body)
+    }
+
+    fn get_air_quality_current_conditions_with_meta(&self) -> Result<ApiResponse<AirQuality>> {
+        self.cached(
+            ProviderEndpoint::AirQualityCurrentConditions,
+            String::new(),
+            || self.provider.get_air_quality_current_conditions_with_meta(),
+        )
+    }
+
+    fn get_air_quality_hourly_forecasts(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<Vec<AirQuality>> {
+        self.get_air_quality_hourly_forecasts_with_meta(period)
+            .map(|response| response.body)
+    }
+
+    fn get_air_quality_hourly_forecasts_with_meta(
+        &self,
+        period: AirQualityHourlyPeriod,
+    ) -> Result<ApiResponse<Vec<AirQuality>>> {
+        self.cached(
+            ProviderEndpoint::AirQualityHourlyForecasts,
+            format!("{:?}", period),
+            || {
+                self.provider
+                    .get_air_quality_hourly_forecasts_with_meta(period)
+            },
+        )
+    }
+
+    fn get_air_quality_daily_forecasts(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<Vec<AirQuality>> {
+        self.get_air_quality_daily_forecasts_with_meta(period)
+            .map(|response| response.body)
+    }
+
+    fn get_air_quality_daily_forecasts_with_meta(
+        &self,
+        period: AirQualityDailyPeriod,
+    ) -> Result<ApiResponse<Vec<AirQuality>>> {
+        self.cached(
+            ProviderEndpoint::AirQualityDailyForecasts,
+            format!("{:?}", period),
+            || {
+                self.provider
+                    .get_air_quality_daily_forecasts_with_meta(period)
+            },
+        )
+    }
+
+    fn get_climo_actuals(&self, year: i32, month: u8, day: u8) -> Result<ClimoActualsAnswer> {
+        self.get_climo_actuals_with_meta(year, month, day)
+            .map(|response| response.body)
+    }
+
+    fn get_climo_actuals_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoActualsAnswer>> {
+        self.cached(
+            ProviderEndpoint::ClimoActuals,
+            format!("{:?}", (year, month, day)),
+            || self.provider.get_climo_actuals_with_meta(year, month, day),
+        )
+    }
+
+    fn get_climo_normals(&self, year: i32, month: u8, day: u8) -> Result<ClimoNormalsAnswer> {
+        self.get_climo_normals_with_meta(year, month, day)
+            .map(|response| response.body)
+    }
+
+    fn get_climo_normals_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoNormalsAnswer>> {
+        self.cached(
+            ProviderEndpoint::ClimoNormals,
+            format!("{:?}", (year, month, day)),
+            || self.provider.get_climo_normals_with_meta(year, month, day),
+        )
+    }
+
+    fn get_climo_records(&self, year: i32, month: u8, day: u8) -> Result<ClimoRecordsAnswer> {
+        self.get_climo_records_with_meta(year, month, day)
+            .map(|response| response.body)
+    }
+
+    fn get_climo_records_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+        day: u8,
+    ) -> Result<ApiResponse<ClimoRecordsAnswer>> {
+        self.cached(
+            ProviderEndpoint::ClimoRecords,
+            format!("{:?}", (year, month, day)),
+            || self.provider.get_climo_records_with_meta(year, month, day),
+        )
+    }
+
+    fn get_climo_month_summary(&self, year: i32, month: u8) -> Result<ClimoSummaryAnswer> {
+        self.get_climo_month_summary_with_meta(year, month)
+            .map(|response| response.body)
+    }
+
+    fn get_climo_month_summary_with_meta(
+        &self,
+        year: i32,
+        month: u8,
+    ) -> Result<ApiResponse<ClimoSummaryAnswer>> {
+        self.cached(
+            ProviderEndpoint::ClimoMonthSummary,
+            format!("{:?}", (year, month)),
+            || self.provider.get_climo_month_summary_with_meta(year, month),
+        )
+    }
+
+    fn get_imagery(&self, resolution: &str) -> Result<ImageryAnswer> {
+        self.get_imagery_with_meta(resolution)
+            .map(|response| response.body)
+    }
+
+    fn get_imagery_with_meta(&self, resolution: &str) -> Result<ApiResponse<ImageryAnswer>> {
+        self.cached(
+            ProviderEndpoint::Imagery,
+            format!("{:?}", resolution),
+            || self.provider.get_imagery_with_meta(resolution),
+        )
+    }
+}
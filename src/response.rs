@@ -0,0 +1,75 @@
+//! Api answers along with their http metadata
+use crate::transport::header;
+use std::time::Duration;
+use url::Url;
+
+/// An api answer with its status, selected headers and latency
+#[derive(Debug, Clone)]
+pub struct ApiResponse<T> {
+    /// http status code
+    pub status: u16,
+    /// number of calls left for the api key, from the `RateLimit-Remaining` header
+    pub rate_limit_remaining: Option<u32>,
+    /// date after which the answer is stale, from the `Expires` header
+    pub expires: Option<String>,
+    /// server date of the answer, from the `Date` header
+    pub date: Option<String>,
+    /// request id given by the server, from the `X-Request-Id` or `X-Correlation-Id` header
+    pub request_id: Option<String>,
+    /// time between sending the request and reading the whole answer
+    pub elapsed: Duration,
+    /// requested url, without the api key
+    pub url: String,
+    /// the typed answer
+    pub body: T,
+}
+
+impl<T> ApiResponse<T> {
+    pub(crate) fn new(
+        url: &Url,
+        headers: &[(String, String)],
+        status: u16,
+        elapsed: Duration,
+        body: T,
+    ) -> Self {
+        let header = |name: &str| header(headers, name).map(str::to_string);
+        ApiResponse {
+            status,
+            rate_limit_remaining: header("RateLimit-Remaining").and_then(|v| v.parse().ok()),
+            expires: header("Expires"),
+            date: header("Date"),
+            request_id: header("X-Request-Id").or_else(|| header("X-Correlation-Id")),
+            elapsed,
+            url: url.to_string(),
+            body,
+        }
+    }
+
+    /// Answer without http metadata, of a provider not sending requests
+    pub(crate) fn from_body(body: T) -> Self {
+        ApiResponse {
+            status: 200,
+            rate_limit_remaining: None,
+            expires: None,
+            date: None,
+            request_id: None,
+            elapsed: Duration::ZERO,
+            url: String::new(),
+            body,
+        }
+    }
+
+    /// Apply `f` to the body, keeping the metadata
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> ApiResponse<U> {
+        ApiResponse {
+            status: self.status,
+            rate_limit_remaining: self.rate_limit_remaining,
+            expires: self.expires,
+            date: self.date,
+            request_id: self.request_id,
+            elapsed: self.elapsed,
+            url: self.url,
+            body: f(self.body),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Polling within a daily call budget
+//!
+//! A `Scheduler` polls endpoints of locations, spacing the calls evenly over the rest of
+//! the UTC day so that the calls of the day never exceed the budget. Each call goes to the
+//! task polled least relative to its weight: its priority during the active hours, or
+//! always when no active hours are set, and 1 outside of them.
+//!
+//! The calls left for the day are the budget minus the requests the scheduler sent, retries
+//! and failovers included, capped by the `RateLimit-Remaining` of the keys, so that calls
+//! made elsewhere with the same keys shrink the plan.
+use crate::periods::{DailyPeriod, HourlyPeriod};
+use crate::types::{AirQuality, CurrentCondition, DailyForecastsAnswer, HourlyForecast};
+use crate::{Accuweather, ParameterResult, Query, SendResult};
+use serde_json::Value;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// An endpoint polled by a `Scheduler`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    CurrentConditions,
+    HourlyForecasts(HourlyPeriod),
+    DailyForecasts(DailyPeriod),
+    AirQuality,
+}
+
+/// The answer of a polled endpoint
+#[derive(Debug)]
+pub enum Answer {
+    CurrentConditions(Vec<CurrentCondition>),
+    HourlyForecasts(Vec<HourlyForecast>),
+    DailyForecasts(DailyForecastsAnswer),
+    AirQuality(AirQuality),
+}
+
+/// A call of the polling plan
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedCall {
+    pub location: i32,
+    pub endpoint: Endpoint,
+    /// time of the call from the start of the plan
+    pub after: Duration,
+}
+
+#[derive(Debug)]
+struct Task {
+    location: i32,
+    endpoint: Endpoint,
+    priority: u32,
+    /// calls of the task, each counting for the inverse of the task weight
+    pass: f64,
+}
+
+/// Polls endpoints of locations within a daily call budget
+#[derive(Debug)]
+pub struct Scheduler {
+    tasks: Vec<Task>,
+    daily_budget: u32,
+    active_hours: Option<(u32, u32)>,
+    day: u64,
+    calls_today: u32,
+    rate_limit_remaining: Option<u32>,
+}
+
+impl Scheduler {
+    /// Create a scheduler sending at most `daily_budget` requests per UTC day
+    pub fn new(daily_budget: u32) -> Self {
+        Scheduler {
+            tasks: Vec::new(),
+            daily_budget,
+            active_hours: None,
+            day: 0,
+            calls_today: 0,
+            rate_limit_remaining: None,
+        }
+    }
+
+    /// Poll an endpoint for each of the locations
+    ///
+    /// Parameters:
+    /// * locations: the location keys
+    /// * endpoint: the polled endpoint
+    /// * priority: weight of the endpoint during active hours, at least 1
+    pub fn add_task(&mut self, locations: &[i32], endpoint: Endpoint, priority: u32) {
+        let pass = self.tasks.iter().map(|task| task.pass).fold(0.0, f64::max);
+        for &location in locations {
+            self.tasks.push(Task {
+                location,
+                endpoint,
+                priority: priority.max(1),
+                pass,
+            });
+        }
+    }
+
+    /// Set the hours of the UTC day when priorities apply, from `start` included to `end`
+    /// excluded, wrapping past midnight when `start` is greater than `end`
+    pub fn set_active_hours(&mut self, hours: Option<(u32, u32)>) {
+        self.active_hours = hours;
+    }
+
+    /// Requests sent by the scheduler since the start of the UTC day
+    pub fn calls_today(&self) -> u32 {
+        self.calls_today
+    }
+
+    /// Calls left for the day at `now`
+    pub fn calls_left(&self, now: SystemTime) -> u32 {
+        if utc_day(now) != self.day {
+            return self.daily_budget;
+        }
+        let left = self.daily_budget.saturating_sub(self.calls_today);
+        match self.rate_limit_remaining {
+            Some(remaining) => left.min(remaining),
+            None => left,
+        }
+    }
+
+    /// Count the requests of a call made at `now`, with the calls left for the keys
+    /// according to the answer
+    pub fn record_call(
+        &mut self,
+        now: SystemTime,
+        requests: u32,
+        rate_limit_remaining: Option<u32>,
+    ) {
+        let day = utc_day(now);
+        if day != self.day {
+            self.day = day;
+            self.calls_today = 0;
+            self.rate_limit_remaining = None;
+        }
+        self.calls_today += requests;
+        if rate_limit_remaining.is_some() {
+            self.rate_limit_remaining = rate_limit_remaining;
+        }
+    }
+
+    /// The calls left for the day from `now`, evenly spaced
+    pub fn plan(&self, now: SystemTime) -> Vec<PlannedCall> {
+        let left = self.calls_left(now);
+        if self.tasks.is_empty() || left == 0 {
+            return Vec::new();
+        }
+        let interval = Duration::from_secs(seconds_to_midnight(now) / u64::from(left));
+        let mut passes: Vec<f64> = self.tasks.iter().map(|task| task.pass).collect();
+        (0..left)
+            .map(|i| {
+                let after = interval * i;
+                let index = self.pick(&mut passes, now + after);
+                PlannedCall {
+                    location: self.tasks[index].location,
+                    endpoint: self.tasks[index].endpoint,
+                    after,
+                }
+            })
+            .collect()
+    }
+
+    /// Poll the endpoints following the plan, giving each answer to `callback` until it
+    /// returns false
+    ///
+    /// When no call is left, waits for the next UTC day.
+    pub fn run<F>(&mut self, client: &Accuweather, mut callback: F)
+    where
+        F: FnMut(&PlannedCall, SendResult<Answer>) -> bool,
+    {
+        loop {
+            let now = SystemTime::now();
+            let left = self.calls_left(now);
+            if self.tasks.is_empty() {
+                return;
+            }
+            if left == 0 {
+                thread::sleep(Duration::from_secs(seconds_to_midnight(now)));
+                continue;
+            }
+            let interval = Duration::from_secs(seconds_to_midnight(now) / u64::from(left));
+            let mut passes: Vec<f64> = self.tasks.iter().map(|task| task.pass).collect();
+            let index = self.pick(&mut passes, now);
+            self.tasks[index].pass = passes[index];
+            let call = PlannedCall {
+                location: self.tasks[index].location,
+                endpoint: self.tasks[index].endpoint,
+                after: Duration::from_secs(0),
+            };
+
+            let sent_before = sent_requests(client);
+            let answer = fetch(client, call.location, call.endpoint);
+            let usage = client.key_usage();
+            let remaining = usage
+                .iter()
+                .filter_map(|key| key.rate_limit_remaining)
+                .fold(None, |sum: Option<u32>, remaining| {
+                    Some(sum.unwrap_or(0) + remaining)
+                });
+            let requests = sent_requests(client).saturating_sub(sent_before) as u32;
+            self.record_call(now, requests, remaining);
+            if !callback(&call, answer) {
+                return;
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    /// Index of the next task at `time`, counting its call in `passes`
+    fn pick(&self, passes: &mut [f64], time: SystemTime) -> usize {
+        let active = match self.active_hours {
+            None => true,
+            Some((start, end)) => {
+                let hour = (time
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+                    % DAY
+                    / 3600) as u32;
+                if start <= end {
+                    start <= hour && hour < end
+                } else {
+                    start <= hour || hour < end
+                }
+            }
+        };
+        let index = (0..passes.len())
+            .min_by(|&a, &b| passes[a].partial_cmp(&passes[b]).unwrap())
+            .unwrap_or(0);
+        let weight = if active {
+            self.tasks[index].priority
+        } else {
+            1
+        };
+        passes[index] += 1.0 / f64::from(weight);
+        index
+    }
+}
+
+fn fetch(client: &Accuweather, location: i32, endpoint: Endpoint) -> SendResult<Answer> {
+    let value = client
+        .fetch(&endpoint_query(client, location, endpoint)?)?
+        .body;
+    parse_answer(client, endpoint, &value)
+}
+
+/// Query of an endpoint for a location
+pub(crate) fn endpoint_query(
+    client: &Accuweather,
+    location: i32,
+    endpoint: Endpoint,
+) -> ParameterResult<Query> {
+    match endpoint {
+        Endpoint::CurrentConditions => client.current_conditions_query(location),
+        Endpoint::HourlyForecasts(period) => client.hourly_forecasts_query(location, period),
+        Endpoint::DailyForecasts(period) => client.daily_forecasts_query(location, period),
+        Endpoint::AirQuality => client.air_quality_current_conditions_query(location),
+    }
+}
+
+/// Parse the json answer of an endpoint
+pub(crate) fn parse_answer(
+    client: &Accuweather,
+    endpoint: Endpoint,
+    value: &Value,
+) -> SendResult<Answer> {
+    Ok(match endpoint {
+        Endpoint::CurrentConditions => Answer::CurrentConditions(client.parse(value)?),
+        Endpoint::HourlyForecasts(_) => Answer::HourlyForecasts(client.parse(value)?),
+        Endpoint::DailyForecasts(_) => Answer::DailyForecasts(client.parse(value)?),
+        Endpoint::AirQuality => Answer::AirQuality(client.parse(value)?),
+    })
+}
+
+fn sent_requests(client: &Accuweather) -> u64 {
+    client.key_usage().iter().map(|key| key.requests).sum()
+}
+
+fn utc_day(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / DAY)
+        .unwrap_or(0)
+}
+
+fn seconds_to_midnight(time: SystemTime) -> u64 {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    DAY - seconds % DAY
+}
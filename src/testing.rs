@@ -0,0 +1,288 @@
+//! Local stub of the Accuweather api, for tests without network
+//!
+//! Built with the `testing` feature. A `StubServer` listens on a local port and answers
+//! every endpoint of the client with the fixtures of the crate `assets` directory, whatever
+//! the location, period or api key. It can answer with errors instead, and delay answers.
+//!
+//! # Example
+//! ```
+//! use accuweather::testing::{Failure, StubServer};
+//!
+//! let server = StubServer::start().unwrap();
+//! let client = server.client(Some(623));
+//! let conditions = client.get_current_conditions().unwrap();
+//! assert_eq!(conditions[0].weather_text, "Sunny");
+//!
+//! server.set_failure(Some(Failure::QuotaExceeded));
+//! assert!(client.get_current_conditions().is_err());
+//! ```
+use crate::Accuweather;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Api key of the clients created by `StubServer::client`
+pub const API_KEY: &str = "stub-api-key";
+
+/// Fixtures of the endpoints, by part of their path, the first match winning
+pub const FIXTURES: &[(&str, &str)] = &[
+    ("/quarters/", include_str!("../assets/quarters5.json")),
+    (
+        "/locations/v1/cities/search",
+        include_str!("../assets/locations_search.json"),
+    ),
+    (
+        "/forecasts/v1/daily/1day/",
+        include_str!("../assets/daily1_details.json"),
+    ),
+    (
+        "/forecasts/v1/daily/",
+        include_str!("../assets/daily5.json"),
+    ),
+    (
+        "/forecasts/v1/hourly/1hour/",
+        include_str!("../assets/hourly1_details.json"),
+    ),
+    (
+        "/forecasts/v1/hourly/",
+        include_str!("../assets/hourly12.json"),
+    ),
+    (
+        "/currentconditions/v1/",
+        include_str!("../assets/conditions_details.json"),
+    ),
+    (
+        "/airquality/v2/currentconditions/",
+        include_str!("../assets/airquality_current.json"),
+    ),
+    (
+        "/airquality/v2/forecasts/hourly/",
+        include_str!("../assets/airquality_hourly12.json"),
+    ),
+    (
+        "/airquality/v2/forecasts/daily/",
+        include_str!("../assets/airquality_daily4.json"),
+    ),
+    (
+        "/climo/v1/summary/",
+        include_str!("../assets/climo_summary.json"),
+    ),
+    (
+        "/climo/v1/actuals/",
+        include_str!("../assets/climo_actuals.json"),
+    ),
+    (
+        "/climo/v1/normals/",
+        include_str!("../assets/climo_normals.json"),
+    ),
+    (
+        "/climo/v1/records/",
+        include_str!("../assets/climo_records.json"),
+    ),
+    (
+        "/imagery/v1/maps/radsat/",
+        include_str!("../assets/imagery480.json"),
+    ),
+];
+
+/// Host of the imagery frames in the fixtures, replaced by the stub server
+const IMAGERY_HOST: &str = "https://sirocco.accuweather.com";
+
+/// Smallest valid png image, served for imagery frames
+const PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4,
+    0x89, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0d, 0x0a, 0x2d, 0xb4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+    0x42, 0x60, 0x82,
+];
+
+/// Error answered by a `StubServer` instead of the fixtures
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// 401, as for an unknown api key
+    Unauthorized,
+    /// 403, as for an endpoint not allowed by the subscription
+    Forbidden,
+    /// 503 with the message of an exceeded daily quota
+    QuotaExceeded,
+    /// 500, as for a server failure
+    ServerError,
+    /// 200 with a truncated json body
+    MalformedJson,
+}
+
+impl Failure {
+    fn answer(self) -> (u16, &'static str) {
+        match self {
+            Failure::Unauthorized => (
+                401,
+                r#"{"Code":"Unauthorized","Message":"Api Authorization failed","Reference":"/stub"}"#,
+            ),
+            Failure::Forbidden => (
+                403,
+                r#"{"Code":"Unauthorized","Message":"Api Authorization failed","Reference":"/stub"}"#,
+            ),
+            Failure::QuotaExceeded => (
+                503,
+                r#"{"Code":"ServiceUnavailable","Message":"The allowed number of requests has been exceeded.","Reference":"/stub"}"#,
+            ),
+            Failure::ServerError => (
+                500,
+                r#"{"Code":"ServerError","Message":"Internal server error","Reference":"/stub"}"#,
+            ),
+            Failure::MalformedJson => (200, r#"[{"LocalObservationDateTime":"2019-08-"#),
+        }
+    }
+}
+
+/// Settings and log of a stub server, shared with its threads
+#[derive(Debug, Default)]
+struct State {
+    failure: Option<Failure>,
+    latency: Duration,
+    quota: Option<u32>,
+    requests: Vec<String>,
+}
+
+/// A local http server answering as the Accuweather api, stopped when dropped
+#[derive(Debug)]
+pub struct StubServer {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl StubServer {
+    /// Start a server on a free local port
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let server = StubServer {
+            address,
+            state: Arc::clone(&state),
+            stopped: Arc::clone(&stopped),
+        };
+        let url = server.url();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                let state = Arc::clone(&state);
+                let url = url.clone();
+                thread::spawn(move || {
+                    let _ = serve(stream, &state, &url);
+                });
+            }
+        });
+        Ok(server)
+    }
+
+    /// Base url of the server, to give to `Accuweather::set_base_url`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// A client of the server, with the `API_KEY` api key
+    pub fn client(&self, location: Option<i32>) -> Accuweather {
+        let mut client = Accuweather::new(API_KEY.to_string(), location, None);
+        client.set_base_url(self.url());
+        client
+    }
+
+    /// Answer every request with `failure`, or with the fixtures when None
+    pub fn set_failure(&self, failure: Option<Failure>) {
+        self.state.lock().unwrap().failure = failure;
+    }
+
+    /// Wait `latency` before answering each request
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.lock().unwrap().latency = latency;
+    }
+
+    /// Answer `quota` more requests, with their `RateLimit-Remaining` header, and the next
+    /// ones with `Failure::QuotaExceeded`. None answers every request.
+    pub fn set_quota(&self, quota: Option<u32>) {
+        self.state.lock().unwrap().quota = quota;
+    }
+
+    /// Paths requested so far, without their query
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // wake the listening thread up so that it sees the flag
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// Answer a request with the fixture of its path, or the configured failure
+fn serve(mut stream: TcpStream, state: &Mutex<State>, url: &str) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let path = target.split('?').next().unwrap_or("/").to_string();
+
+    let (failure, latency, remaining) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(path.clone());
+        let remaining = state.quota.map(|quota| quota.saturating_sub(1));
+        let failure = match state.quota {
+            Some(0) => Some(Failure::QuotaExceeded),
+            _ => state.failure,
+        };
+        if failure.is_none() {
+            state.quota = remaining;
+        }
+        (failure, state.latency, remaining)
+    };
+    thread::sleep(latency);
+
+    let (status, content_type, body) = match failure {
+        Some(failure) => {
+            let (status, body) = failure.answer();
+            (status, "application/json", body.as_bytes().to_vec())
+        }
+        None if path.ends_with(".png") => (200, "image/png", PNG.to_vec()),
+        None => match FIXTURES.iter().find(|(prefix, _)| path.contains(prefix)) {
+            Some((_, fixture)) => (
+                200,
+                "application/json",
+                fixture.replace(IMAGERY_HOST, url).into_bytes(),
+            ),
+            None => (
+                404,
+                "application/json",
+                br#"{"Code":"ResourceNotFound","Message":"Not found","Reference":"/stub"}"#
+                    .to_vec(),
+            ),
+        },
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    if let (None, Some(remaining)) = (failure, remaining) {
+        head.push_str(&format!("RateLimit-Remaining: {}\r\n", remaining));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&body)
+}
@@ -0,0 +1,378 @@
+//! Http transports of the client
+//!
+//! The client sends its GET requests through a `Transport`, which returns the status,
+//! headers and body of the answer. Key selection, retries, cassettes and parsing sit on top
+//! of it. Implementations are built with features:
+//! * `reqwest-blocking` (default): `ReqwestTransport`, on reqwest 0.9
+//! * `ureq`: `UreqTransport`, on ureq 2
+//! * `reqwest-async`: `ReqwestAsyncTransport`, an `AsyncTransport` on reqwest 0.12 and
+//!   tokio, used by the `_async` methods of the client
+//!
+//! Without any of them, requests fail until a transport is set with
+//! `Accuweather::set_transport`, or `Accuweather::set_async_transport` for the `_async`
+//! methods.
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use std::{error, fmt};
+
+/// A GET request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// full url, with the api key in query parameter auth mode
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// time allowed to send the request and read the whole answer, None for no limit
+    pub timeout: Option<Duration>,
+}
+
+/// The answer to a request, whatever its status
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Value of a header, whose name is case insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+
+    /// The body as text, invalid utf-8 replaced
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// Value of a header in `headers`, whose name is case insensitive
+pub(crate) fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Error of a request that got no answer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransportError {
+    /// no answer within the timeout
+    Timeout(String),
+    /// the server can't be reached, or the connection broke
+    Connection(String),
+    /// any other failure, such as an invalid url
+    Other(String),
+}
+
+impl TransportError {
+    /// Whether the request may succeed when retried
+    pub fn is_transient(&self) -> bool {
+        match self {
+            TransportError::Timeout(_) | TransportError::Connection(_) => true,
+            TransportError::Other(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportError::Timeout(message) => write!(f, "timeout: {}", message),
+            TransportError::Connection(message) => write!(f, "connection error: {}", message),
+            TransportError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for TransportError {}
+
+/// Sends the GET requests of a client
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// Send a request
+    ///
+    /// Returns a Result with either the answer, error statuses included, or the error of a
+    /// request that got no answer
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, TransportError>;
+}
+
+/// Answer of an `AsyncTransport`
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, TransportError>> + Send + 'a>>;
+
+/// Wait of an `AsyncTransport`
+pub type SleepFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// Sends GET requests without blocking, as `Transport` does
+pub trait AsyncTransport: fmt::Debug + Send + Sync {
+    fn get<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a>;
+
+    /// Wait `duration` on the timer of the runtime of the transport, before a retry
+    ///
+    /// Returns None when the transport has no timer, the default, in which case requests
+    /// are not retried.
+    fn sleep(&self, _duration: Duration) -> Option<SleepFuture<'_>> {
+        None
+    }
+}
+
+/// Transport of clients built without any transport feature
+#[cfg(not(any(feature = "reqwest-blocking", feature = "ureq")))]
+#[derive(Debug)]
+pub(crate) struct NoTransport;
+
+#[cfg(not(any(feature = "reqwest-blocking", feature = "ureq")))]
+impl Transport for NoTransport {
+    fn get(&self, _request: &HttpRequest) -> Result<HttpResponse, TransportError> {
+        Err(TransportError::Other(
+            "no http transport, enable a transport feature or set one".to_string(),
+        ))
+    }
+}
+
+/// The transport of new clients: reqwest, else ureq, else none
+pub(crate) fn default_transport() -> Box<dyn Transport> {
+    #[cfg(feature = "reqwest-blocking")]
+    return Box::new(ReqwestTransport::new());
+    #[cfg(all(feature = "ureq", not(feature = "reqwest-blocking")))]
+    return Box::new(UreqTransport::new());
+    #[cfg(not(any(feature = "reqwest-blocking", feature = "ureq")))]
+    return Box::new(NoTransport);
+}
+
+/// Async transport of clients built without the `reqwest-async` feature
+#[cfg(not(feature = "reqwest-async"))]
+#[derive(Debug)]
+pub(crate) struct NoAsyncTransport;
+
+#[cfg(not(feature = "reqwest-async"))]
+impl AsyncTransport for NoAsyncTransport {
+    fn get<'a>(&'a self, _request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async {
+            Err(TransportError::Other(
+                "no async http transport, enable the reqwest-async feature or set one".to_string(),
+            ))
+        })
+    }
+}
+
+/// The async transport of new clients: reqwest 0.12, else none
+pub(crate) fn default_async_transport() -> Box<dyn AsyncTransport> {
+    #[cfg(feature = "reqwest-async")]
+    return Box::new(ReqwestAsyncTransport::new());
+    #[cfg(not(feature = "reqwest-async"))]
+    return Box::new(NoAsyncTransport);
+}
+
+/// Transport on the blocking reqwest 0.9 client
+#[cfg(feature = "reqwest-blocking")]
+#[derive(Debug, Default)]
+pub struct ReqwestTransport {
+    /// a client for each timeout used, reqwest 0.9 setting them by client
+    clients: std::sync::Mutex<Vec<(Option<Duration>, reqwest::Client)>>,
+}
+
+#[cfg(feature = "reqwest-blocking")]
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        ReqwestTransport::default()
+    }
+
+    fn client(&self, timeout: Option<Duration>) -> Result<reqwest::Client, TransportError> {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some((_, client)) = clients.iter().find(|(t, _)| *t == timeout) {
+            return Ok(client.clone());
+        }
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|error| TransportError::Other(error.to_string()))?;
+        clients.push((timeout, client.clone()));
+        Ok(client)
+    }
+}
+
+#[cfg(feature = "reqwest-blocking")]
+impl Transport for ReqwestTransport {
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, TransportError> {
+        let mut builder = self.client(request.timeout)?.get(&request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let error = |error: reqwest::Error| {
+            if error.is_timeout() {
+                TransportError::Timeout(error.to_string())
+            } else if io_failure(&error) {
+                TransportError::Connection(error.to_string())
+            } else {
+                TransportError::Other(error.to_string())
+            }
+        };
+        let mut response = builder.send().map_err(error)?;
+        let mut body = Vec::new();
+        response.copy_to(&mut body).map_err(error)?;
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            headers: response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body,
+        })
+    }
+}
+
+/// Whether a reqwest 0.9 error comes from connecting or reading, not from building the request
+///
+/// `is_http` also covers invalid requests, which would fail again when retried.
+#[cfg(feature = "reqwest-blocking")]
+fn io_failure(error: &reqwest::Error) -> bool {
+    let mut source = error
+        .get_ref()
+        .map(|error| error as &(dyn error::Error + 'static));
+    while let Some(error) = source {
+        if error.is::<std::io::Error>() {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// Transport on a ureq 2 agent
+#[cfg(feature = "ureq")]
+#[derive(Debug)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "ureq")]
+impl UreqTransport {
+    pub fn new() -> Self {
+        UreqTransport::with_agent(ureq::Agent::new())
+    }
+
+    /// Send the requests with a configured agent
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        UreqTransport { agent }
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl Default for UreqTransport {
+    fn default() -> Self {
+        UreqTransport::new()
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl Transport for UreqTransport {
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, TransportError> {
+        let mut builder = self.agent.get(&request.url);
+        for (name, value) in &request.headers {
+            builder = builder.set(name, value);
+        }
+        if let Some(timeout) = request.timeout {
+            builder = builder.timeout(timeout);
+        }
+        let response = match builder.call() {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(error)) => {
+                return Err(match error.kind() {
+                    ureq::ErrorKind::Dns
+                    | ureq::ErrorKind::ConnectionFailed
+                    | ureq::ErrorKind::Io => TransportError::Connection(error.to_string()),
+                    _ => TransportError::Other(error.to_string()),
+                })
+            }
+        };
+        let status = response.status();
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let mut body = Vec::new();
+        std::io::Read::read_to_end(&mut response.into_reader(), &mut body).map_err(|error| {
+            match error.kind() {
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => {
+                    TransportError::Timeout(error.to_string())
+                }
+                _ => TransportError::Connection(error.to_string()),
+            }
+        })?;
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Async transport on a reqwest 0.12 client
+#[cfg(feature = "reqwest-async")]
+#[derive(Debug, Default)]
+pub struct ReqwestAsyncTransport {
+    client: reqwest_async::Client,
+}
+
+#[cfg(feature = "reqwest-async")]
+impl ReqwestAsyncTransport {
+    pub fn new() -> Self {
+        ReqwestAsyncTransport::default()
+    }
+
+    /// Send the requests with a configured client
+    pub fn with_client(client: reqwest_async::Client) -> Self {
+        ReqwestAsyncTransport { client }
+    }
+}
+
+#[cfg(feature = "reqwest-async")]
+impl AsyncTransport for ReqwestAsyncTransport {
+    fn get<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let error = |error: reqwest_async::Error| {
+                if error.is_timeout() {
+                    TransportError::Timeout(error.to_string())
+                } else if error.is_connect() || error.is_request() || error.is_body() {
+                    TransportError::Connection(error.to_string())
+                } else {
+                    TransportError::Other(error.to_string())
+                }
+            };
+            let mut builder = self.client.get(&request.url);
+            for (name, value) in &request.headers {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+            let response = builder.send().await.map_err(error)?;
+            let status = response.status().as_u16();
+            let headers = response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect();
+            let body = response.bytes().await.map_err(error)?.to_vec();
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> Option<SleepFuture<'_>> {
+        Some(Box::pin(tokio::time::sleep(duration)))
+    }
+}
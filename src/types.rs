@@ -1,18 +1,27 @@
 //! All types needed for Accuweather Api
 extern crate serde_derive;
 extern crate serde_json;
+use crate::icons::WeatherIcon;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::fmt;
 
 /// Type for most of Accuweather forecasts value
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct AccuweatherMeasurement {
     /// the actual value
+    #[serde(alias = "value")]
     pub value: f32,
     /// the unit
+    #[serde(alias = "unit")]
     pub unit: String,
     /// Accuweather internal unit representation
-    unit_type: i32,
+    #[serde(alias = "unitType")]
+    pub unit_type: i32,
+    /// description of the value, given for real feel temperatures
+    #[serde(alias = "phrase")]
+    pub phrase: Option<String>,
 }
 
 impl AccuweatherMeasurement {
@@ -34,19 +43,57 @@ impl fmt::Display for AccuweatherMeasurement {
 }
 
 /// Represntation of Air and Pollen information
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct AirAndPollen {
     pub name: String,
     pub value: i32,
     pub category: String,
-    category_value: i32,
+    /// numeric level of the category, from 1 (lowest) upwards
+    pub category_value: i32,
+    /// dominant pollutant or pollen type, empty when not given
     #[serde(default = "air_pollen_default_type")]
-    r#type: String,
+    pub r#type: String,
+}
+
+/// Typed view of the air and pollen information of a daily forecast
+#[derive(Debug)]
+pub struct AirAndPollenSummary<'a> {
+    pub air_quality: Option<&'a AirAndPollen>,
+    pub grass: Option<&'a AirAndPollen>,
+    pub mold: Option<&'a AirAndPollen>,
+    pub ragweed: Option<&'a AirAndPollen>,
+    pub tree: Option<&'a AirAndPollen>,
+    pub uv_index: Option<&'a AirAndPollen>,
+}
+
+impl<'a> AirAndPollenSummary<'a> {
+    fn new(air_and_pollen: &'a [AirAndPollen]) -> Self {
+        let find = |name: &str| air_and_pollen.iter().find(|a| a.name == name);
+        AirAndPollenSummary {
+            air_quality: find("AirQuality"),
+            grass: find("Grass"),
+            mold: find("Mold"),
+            ragweed: find("Ragweed"),
+            tree: find("Tree"),
+            uv_index: find("UVIndex"),
+        }
+    }
+
+    /// Pollens (grass, mold, ragweed and tree), highest category level first
+    pub fn pollens_by_risk(&self) -> Vec<&'a AirAndPollen> {
+        let mut pollens: Vec<&AirAndPollen> = [self.grass, self.mold, self.ragweed, self.tree]
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        pollens.sort_by_key(|p| std::cmp::Reverse(p.category_value));
+        pollens
+    }
 }
 
 /// Representation of wind direction
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct WindDirection {
     pub degrees: f32,
@@ -55,7 +102,7 @@ pub struct WindDirection {
 }
 
 /// Represnetation of Wind in forecasts api
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Wind {
     pub speed: AccuweatherMeasurement,
@@ -63,46 +110,75 @@ pub struct Wind {
 }
 
 /// Representation of wind gust in daily forecasts api
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct DailyWindGust {
-    speed: AccuweatherMeasurement,
+    pub speed: AccuweatherMeasurement,
+    pub direction: Option<WindDirection>,
 }
 
 /// Representation of temperature in daily forecast
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Temperature {
     pub maximum: AccuweatherMeasurement,
     pub minimum: AccuweatherMeasurement,
 }
 
+/// Representation of a relative humidity range in daily forecast api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct RelativeHumidityRange {
+    pub minimum: i32,
+    pub maximum: i32,
+    pub average: i32,
+}
+
+/// Representation of a temperature range with its average in daily forecast api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct TemperatureRange {
+    pub minimum: AccuweatherMeasurement,
+    pub maximum: AccuweatherMeasurement,
+    pub average: AccuweatherMeasurement,
+}
+
 /// Represention of forecast for a day part (either night or day) in daily forecast api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct DayPartForecast {
     pub cloud_cover: i32,
+    pub evapotranspiration: Option<AccuweatherMeasurement>,
+    pub has_precipitation: Option<bool>,
+    pub hours_of_ice: f32,
     pub hours_of_precipitation: f32,
     pub hours_of_rain: f32,
+    pub hours_of_snow: f32,
     pub ice: AccuweatherMeasurement,
     pub ice_probability: i32,
-    pub icon: i32,
+    pub icon: WeatherIcon,
     pub icon_phrase: String,
     pub long_phrase: String,
+    pub precipitation_intensity: Option<String>,
     pub precipitation_probability: i32,
+    pub precipitation_type: Option<PrecipitationType>,
     pub rain: AccuweatherMeasurement,
     pub rain_probability: i32,
+    pub relative_humidity: Option<RelativeHumidityRange>,
     pub short_phrase: String,
     pub snow: AccuweatherMeasurement,
     pub snow_probability: i32,
+    pub solar_irradiance: Option<AccuweatherMeasurement>,
     pub thunderstorm_probability: i32,
     pub total_liquid: AccuweatherMeasurement,
+    pub wet_bulb_globe_temperature: Option<TemperatureRange>,
+    pub wet_bulb_temperature: Option<TemperatureRange>,
     pub wind: Wind,
     pub wind_gust: Wind,
 }
 
 /// Representation of degree day summary in daily forecast api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct DegreeDaySummary {
     pub heating: AccuweatherMeasurement,
@@ -110,29 +186,89 @@ pub struct DegreeDaySummary {
 }
 
 /// Representation of Sun information in daily forecast api.
-#[derive(Deserialize, Debug)]
+///
+/// Rise and set are `None` during polar day and polar night.
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Sun {
-    pub rise: String,
-    pub epoch_rise: i64,
-    pub set: String,
-    pub epoch_set: i64,
+    pub rise: Option<String>,
+    pub epoch_rise: Option<i64>,
+    pub set: Option<String>,
+    pub epoch_set: Option<i64>,
 }
 
 /// Representation of Moon information in daily forecast api.
-#[derive(Deserialize, Debug)]
+///
+/// Rise or set is `None` on days without moonrise or moonset.
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Moon {
-    pub rise: String,
-    pub epoch_rise: i64,
-    pub set: String,
-    pub epoch_set: i64,
-    pub phase: String,
+    pub rise: Option<String>,
+    pub epoch_rise: Option<i64>,
+    pub set: Option<String>,
+    pub epoch_set: Option<i64>,
+    pub phase: MoonPhase,
     pub age: i32,
 }
 
+impl Moon {
+    /// Estimated illuminated fraction of the moon (0.0 to 1.0) from its age in days
+    pub fn illumination(&self) -> f32 {
+        let angle = 2.0 * std::f32::consts::PI * self.age as f32 / SYNODIC_MONTH;
+        (1.0 - angle.cos()) / 2.0
+    }
+}
+
+/// Mean length of a lunar cycle, in days
+const SYNODIC_MONTH: f32 = 29.530_588;
+
+/// Phase of the moon in daily forecast api.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    First,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    Last,
+    WaningCrescent,
+    Unknown(String),
+}
+
+impl From<String> for MoonPhase {
+    fn from(phase: String) -> Self {
+        match phase.as_str() {
+            "New" => MoonPhase::New,
+            "WaxingCrescent" => MoonPhase::WaxingCrescent,
+            "First" => MoonPhase::First,
+            "WaxingGibbous" => MoonPhase::WaxingGibbous,
+            "Full" => MoonPhase::Full,
+            "WaningGibbous" => MoonPhase::WaningGibbous,
+            "Last" => MoonPhase::Last,
+            "WaningCrescent" => MoonPhase::WaningCrescent,
+            _ => MoonPhase::Unknown(phase),
+        }
+    }
+}
+
+impl MoonPhase {
+    /// Typical illuminated fraction of the moon (0.0 to 1.0) during the phase
+    pub fn illumination(&self) -> Option<f32> {
+        match self {
+            MoonPhase::New => Some(0.0),
+            MoonPhase::WaxingCrescent | MoonPhase::WaningCrescent => Some(0.25),
+            MoonPhase::First | MoonPhase::Last => Some(0.5),
+            MoonPhase::WaxingGibbous | MoonPhase::WaningGibbous => Some(0.75),
+            MoonPhase::Full => Some(1.0),
+            MoonPhase::Unknown(_) => None,
+        }
+    }
+}
+
 /// Representation of daily forecast
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct DailyForecast {
     pub air_and_pollen: Vec<AirAndPollen>,
@@ -153,26 +289,62 @@ pub struct DailyForecast {
 }
 
 /// Representation of the Headline part of daily forecast api answer
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Headline {
     pub effective_date: String,
     pub effective_epoch_date: i64,
     pub severity: i32,
     pub text: String,
-    pub category: String,
+    pub category: HeadlineCategory,
     pub end_date: String,
     pub end_epoch_date: i64,
     pub mobile_link: String,
     pub link: String,
 }
 
+/// Category of the Headline in daily forecast api.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum HeadlineCategory {
+    Rain,
+    Snow,
+    Ice,
+    Thunderstorm,
+    Wind,
+    Heat,
+    Cold,
+    Fog,
+    AirQuality,
+    Unknown(String),
+}
+
+impl From<String> for HeadlineCategory {
+    fn from(category: String) -> Self {
+        match category.as_str() {
+            "rain" => HeadlineCategory::Rain,
+            "snow" => HeadlineCategory::Snow,
+            "ice" => HeadlineCategory::Ice,
+            "thunderstorm" => HeadlineCategory::Thunderstorm,
+            "wind" => HeadlineCategory::Wind,
+            "heat" => HeadlineCategory::Heat,
+            "cold" => HeadlineCategory::Cold,
+            "fog" => HeadlineCategory::Fog,
+            "air quality" => HeadlineCategory::AirQuality,
+            _ => HeadlineCategory::Unknown(category),
+        }
+    }
+}
+
 /// Representation of daily forecast api answer
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct DailyForecastsAnswer {
     pub headline: Headline,
     pub daily_forecasts: Vec<DailyForecast>,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
 fn air_pollen_default_type() -> String {
@@ -180,42 +352,145 @@ fn air_pollen_default_type() -> String {
 }
 
 /// Representation of an hourly forecast
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct HourlyForecast {
-    pub ceiling: AccuweatherMeasurement,
+    pub ceiling: Option<AccuweatherMeasurement>,
     pub cloud_cover: i32,
     pub date_time: String,
     pub dew_point: AccuweatherMeasurement,
     pub epoch_date_time: i64,
+    pub evapotranspiration: Option<AccuweatherMeasurement>,
+    pub has_precipitation: Option<bool>,
     pub ice: AccuweatherMeasurement,
     pub ice_probability: i32,
     pub icon_phrase: String,
+    pub indoor_relative_humidity: Option<i32>,
     pub is_daylight: bool,
     pub link: String,
     pub mobile_link: String,
+    pub precipitation_intensity: Option<String>,
     pub precipitation_probability: i32,
+    pub precipitation_type: Option<PrecipitationType>,
     pub rain: AccuweatherMeasurement,
     pub rain_probability: i32,
     pub real_feel_temperature: AccuweatherMeasurement,
+    pub real_feel_temperature_shade: Option<AccuweatherMeasurement>,
     pub relative_humidity: i32,
     pub snow: AccuweatherMeasurement,
     pub snow_probability: i32,
+    pub solar_irradiance: Option<AccuweatherMeasurement>,
     pub temperature: AccuweatherMeasurement,
+    pub thunderstorm_probability: Option<i32>,
     pub total_liquid: AccuweatherMeasurement,
     #[serde(rename = "UVIndex")]
     pub uv_index: i32,
     #[serde(rename = "UVIndexText")]
-    pub uv_index_text: String,
+    pub uv_index_text: UvIndexText,
     pub visibility: AccuweatherMeasurement,
-    pub weather_icon: i32,
+    pub weather_icon: WeatherIcon,
+    pub wet_bulb_globe_temperature: Option<AccuweatherMeasurement>,
     pub wet_bulb_temperature: AccuweatherMeasurement,
     pub wind: Wind,
     pub wind_gust: DailyWindGust,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Representation of a quarter-day forecast
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct QuarterForecast {
+    pub date: String,
+    pub effective_date: String,
+    pub effective_epoch_date: i64,
+    /// 0: morning, 1: afternoon, 2: evening, 3: overnight
+    pub quarter: i32,
+    pub icon: WeatherIcon,
+    pub icon_phrase: String,
+    pub phrase: String,
+    pub temperature: Temperature,
+    pub real_feel_temperature: Temperature,
+    pub dew_point: AccuweatherMeasurement,
+    pub relative_humidity: i32,
+    pub wind: Wind,
+    pub wind_gust: Wind,
+    pub visibility: AccuweatherMeasurement,
+    pub ceiling: Option<AccuweatherMeasurement>,
+    pub cloud_cover: i32,
+    pub precipitation_probability: i32,
+    pub thunderstorm_probability: i32,
+    pub rain_probability: i32,
+    pub snow_probability: i32,
+    pub ice_probability: i32,
+    pub total_liquid: AccuweatherMeasurement,
+    pub rain: AccuweatherMeasurement,
+    pub snow: AccuweatherMeasurement,
+    pub ice: AccuweatherMeasurement,
+    pub mobile_link: String,
+    pub link: String,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl QuarterForecast {
+    /// English name of the quarter
+    pub fn name(&self) -> Option<&'static str> {
+        match self.quarter {
+            0 => Some("Morning"),
+            1 => Some("Afternoon"),
+            2 => Some("Evening"),
+            3 => Some("Overnight"),
+            _ => None,
+        }
+    }
+}
+
+/// UV index category, as given in english by hourly forecasts and current condition api.
+///
+/// Localized texts are kept as `Unknown`, use `UvIndexText::from_index` to get the
+/// category from the numeric index instead.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum UvIndexText {
+    Low,
+    Moderate,
+    High,
+    VeryHigh,
+    Extreme,
+    Unknown(String),
+}
+
+impl From<String> for UvIndexText {
+    fn from(text: String) -> Self {
+        match text.as_str() {
+            "Low" => UvIndexText::Low,
+            "Moderate" => UvIndexText::Moderate,
+            "High" => UvIndexText::High,
+            "Very High" => UvIndexText::VeryHigh,
+            "Extreme" => UvIndexText::Extreme,
+            _ => UvIndexText::Unknown(text),
+        }
+    }
+}
+
+impl UvIndexText {
+    /// Category of a numeric UV index
+    pub fn from_index(index: i32) -> Self {
+        match index {
+            i32::MIN..=2 => UvIndexText::Low,
+            3..=5 => UvIndexText::Moderate,
+            6..=7 => UvIndexText::High,
+            8..=10 => UvIndexText::VeryHigh,
+            _ => UvIndexText::Extreme,
+        }
+    }
 }
 
 /// Representation of LocalSource for current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct LocalSource {
     pub id: i32,
@@ -224,7 +499,7 @@ pub struct LocalSource {
 }
 
 /// Representation of a measurement in current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct ConditionMeasurement {
     pub metric: AccuweatherMeasurement,
@@ -232,15 +507,59 @@ pub struct ConditionMeasurement {
 }
 
 /// Representation of pressure tendency in current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct PressureTendency {
     pub localized_text: String,
-    pub code: String,
+    pub code: PressureTendencyCode,
+}
+
+/// Code of the pressure tendency in current condition api.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum PressureTendencyCode {
+    Falling,
+    Steady,
+    Rising,
+    Unknown(String),
+}
+
+impl From<String> for PressureTendencyCode {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "F" => PressureTendencyCode::Falling,
+            "S" => PressureTendencyCode::Steady,
+            "R" => PressureTendencyCode::Rising,
+            _ => PressureTendencyCode::Unknown(code),
+        }
+    }
+}
+
+/// Type of precipitation in current condition api.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum PrecipitationType {
+    Rain,
+    Snow,
+    Ice,
+    Mixed,
+    Unknown(String),
+}
+
+impl From<String> for PrecipitationType {
+    fn from(precipitation_type: String) -> Self {
+        match precipitation_type.as_str() {
+            "Rain" => PrecipitationType::Rain,
+            "Snow" => PrecipitationType::Snow,
+            "Ice" => PrecipitationType::Ice,
+            "Mixed" => PrecipitationType::Mixed,
+            _ => PrecipitationType::Unknown(precipitation_type),
+        }
+    }
 }
 
 /// Reprensentation of preciipiation summary in current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct PrecipitationSummary {
     pub precipitation: ConditionMeasurement,
@@ -254,7 +573,7 @@ pub struct PrecipitationSummary {
 }
 
 /// Representation of temperature summary in current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct TemperatureSummary {
     pub past6_hour_range: TemperatureSummaryRange,
@@ -263,7 +582,7 @@ pub struct TemperatureSummary {
 }
 
 /// Representation of temperature summary during a rang of time in current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct TemperatureSummaryRange {
     pub minimum: ConditionMeasurement,
@@ -271,7 +590,7 @@ pub struct TemperatureSummaryRange {
 }
 
 /// Representation of wind in current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct WindCondition {
     pub speed: ConditionMeasurement,
@@ -279,48 +598,367 @@ pub struct WindCondition {
 }
 
 /// Representation of wind gust in current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct ConditionWindGust {
     pub speed: ConditionMeasurement,
 }
 
 /// Representation of Current Condion in current condition api.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct CurrentCondition {
     pub local_observation_date_time: String,
     pub epoch_time: i64,
     pub weather_text: String,
-    pub weather_icon: i32,
+    pub weather_icon: WeatherIcon,
     pub local_source: Option<LocalSource>,
     pub is_day_time: bool,
     pub temperature: ConditionMeasurement,
     pub real_feel_temperature: ConditionMeasurement,
     pub real_feel_temperature_shade: ConditionMeasurement,
     pub relative_humidity: i32,
+    pub indoor_relative_humidity: Option<i32>,
     pub dew_point: ConditionMeasurement,
     pub wind: WindCondition,
     pub wind_gust: ConditionWindGust,
     #[serde(rename = "UVIndex")]
     pub uv_index: i32,
     #[serde(rename = "UVIndexText")]
-    pub uv_index_text: String,
+    pub uv_index_text: UvIndexText,
     pub visibility: ConditionMeasurement,
     pub obstructions_to_visibility: String,
     pub cloud_cover: i32,
-    pub ceiling: ConditionMeasurement,
+    pub ceiling: Option<ConditionMeasurement>,
     pub pressure: ConditionMeasurement,
     pub pressure_tendency: PressureTendency,
     pub past24_hour_temperature_departure: ConditionMeasurement,
     pub apparent_temperature: ConditionMeasurement,
     pub wind_chill_temperature: ConditionMeasurement,
     pub wet_bulb_temperature: ConditionMeasurement,
+    pub wet_bulb_globe_temperature: Option<ConditionMeasurement>,
     pub precip1hr: ConditionMeasurement,
     pub precipitation_summary: PrecipitationSummary,
     pub temperature_summary: TemperatureSummary,
     pub mobile_link: String,
     pub link: String,
     pub has_precipitation: bool,
-    pub precipitation_type: Option<String>,
+    pub precipitation_type: Option<PrecipitationType>,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Representation of a pollutant in air quality api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Pollutant {
+    /// Pollutant code: `PM2.5`, `PM10`, `O3`, `NO2`, `SO2` or `CO`
+    pub r#type: String,
+    pub name: String,
+    pub index: f32,
+    pub concentration: AccuweatherMeasurement,
+    pub source: String,
+    pub hazard_statement: String,
+}
+
+/// Representation of air quality in air quality current conditions and forecasts api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AirQuality {
+    pub date: String,
+    pub epoch_date: i64,
+    pub overall_index: f32,
+    pub category: String,
+    pub category_color: String,
+    pub hazard_statement: String,
+    pub dominant_pollutant: String,
+    pub pollutants: Vec<Pollutant>,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl AirQuality {
+    /// Get a pollutant by its code (e.g. `PM2.5` or `O3`)
+    pub fn pollutant(&self, code: &str) -> Option<&Pollutant> {
+        self.pollutants.iter().find(|p| p.r#type == code)
+    }
+}
+
+/// Representation of temperatures in climo api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoTemperatures {
+    pub maximum: AccuweatherMeasurement,
+    pub minimum: AccuweatherMeasurement,
+    pub average: AccuweatherMeasurement,
+}
+
+/// Representation of degree days in climo api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoDegreeDays {
+    pub heating: AccuweatherMeasurement,
+    pub cooling: AccuweatherMeasurement,
+}
+
+/// Representation of the climatology of a day (either actuals or normals) in climo api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoDay {
+    pub temperatures: ClimoTemperatures,
+    pub precipitation: AccuweatherMeasurement,
+    pub snowfall: AccuweatherMeasurement,
+    pub degree_days: ClimoDegreeDays,
+}
+
+/// Representation of a record in climo api, with the year it was set.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoRecord {
+    #[serde(flatten)]
+    pub measurement: AccuweatherMeasurement,
+    pub year: i32,
+}
+
+/// Representation of temperature records in climo api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoRecordTemperatures {
+    pub maximum: ClimoRecord,
+    pub minimum: ClimoRecord,
+}
+
+/// Representation of the records of a day in climo api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoRecordDay {
+    pub temperatures: ClimoRecordTemperatures,
+    pub precipitation: ClimoRecord,
+    pub snowfall: ClimoRecord,
+}
+
+/// Representation of climo actuals api answer
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoActualsAnswer {
+    pub date: String,
+    pub epoch_date: i64,
+    pub actuals: ClimoDay,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Representation of climo normals api answer
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoNormalsAnswer {
+    pub date: String,
+    pub epoch_date: i64,
+    pub normals: ClimoDay,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Representation of climo records api answer
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoRecordsAnswer {
+    pub date: String,
+    pub epoch_date: i64,
+    pub records: ClimoRecordDay,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Representation of climo month summary api answer
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClimoSummaryAnswer {
+    pub year: i32,
+    pub month: i32,
+    pub actuals: Vec<ClimoActualsAnswer>,
+    pub normals: Vec<ClimoNormalsAnswer>,
+    pub records: Vec<ClimoRecordsAnswer>,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Departure of a daily forecast from climatological normals.
+///
+/// Every value is `forecast - normal`, in the units of the compared values.
+#[derive(Debug)]
+pub struct ClimoAnomaly {
+    pub maximum: f32,
+    pub minimum: f32,
+    pub precipitation: f32,
+    pub snowfall: f32,
+    pub heating_degree_days: f32,
+    pub cooling_degree_days: f32,
+}
+
+impl DailyForecast {
+    /// Typed view of `air_and_pollen`
+    pub fn air_and_pollen_summary(&self) -> AirAndPollenSummary<'_> {
+        AirAndPollenSummary::new(&self.air_and_pollen)
+    }
+
+    /// Compare the forecast with the normals of the same day.
+    ///
+    /// Both must have been fetched with the same units, which is the case for the
+    /// forecasts and climo methods of the client.
+    pub fn anomalies(&self, normals: &ClimoDay) -> ClimoAnomaly {
+        ClimoAnomaly {
+            maximum: self.temperature.maximum.value - normals.temperatures.maximum.value,
+            minimum: self.temperature.minimum.value - normals.temperatures.minimum.value,
+            precipitation: self.day.total_liquid.value + self.night.total_liquid.value
+                - normals.precipitation.value,
+            snowfall: self.day.snow.value + self.night.snow.value - normals.snowfall.value,
+            heating_degree_days: self.degree_day_summary.heating.value
+                - normals.degree_days.heating.value,
+            cooling_degree_days: self.degree_day_summary.cooling.value
+                - normals.degree_days.cooling.value,
+        }
+    }
+}
+
+/// Representation of an image in imagery api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageryImage {
+    pub date: String,
+    pub url: String,
+}
+
+/// Representation of a radar or satellite layer in imagery api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageryLayer {
+    pub size: String,
+    pub images: Vec<ImageryImage>,
+}
+
+/// Representation of imagery api answer
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageryAnswer {
+    pub radar: ImageryLayer,
+    pub satellite: ImageryLayer,
+    pub mobile_link: String,
+    pub link: String,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Kind of imagery a frame belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageryType {
+    Radar,
+    Satellite,
+}
+
+/// A single timestamped radar or satellite image
+#[derive(Clone, Debug)]
+pub struct ImageryFrame {
+    pub kind: ImageryType,
+    pub url: String,
+    pub date: String,
+    /// image size as `WIDTHxHEIGHT`
+    pub size: String,
+}
+
+impl ImageryLayer {
+    /// Frames of the layer, oldest first
+    ///
+    /// Dates of a layer are given with the same utc offset, so they sort as strings.
+    fn frames(&self, kind: ImageryType) -> Vec<ImageryFrame> {
+        let mut frames: Vec<ImageryFrame> = self
+            .images
+            .iter()
+            .map(|image| ImageryFrame {
+                kind,
+                url: image.url.clone(),
+                date: image.date.clone(),
+                size: self.size.clone(),
+            })
+            .collect();
+        frames.sort_by(|a, b| a.date.cmp(&b.date));
+        frames
+    }
+}
+
+impl ImageryAnswer {
+    /// Radar frames, oldest first
+    pub fn radar_frames(&self) -> Vec<ImageryFrame> {
+        self.radar.frames(ImageryType::Radar)
+    }
+
+    /// Satellite frames, oldest first
+    pub fn satellite_frames(&self) -> Vec<ImageryFrame> {
+        self.satellite.frames(ImageryType::Satellite)
+    }
+
+    /// Radar then satellite frames
+    pub fn frames(&self) -> Vec<ImageryFrame> {
+        let mut frames = self.radar_frames();
+        frames.extend(self.satellite_frames());
+        frames
+    }
+}
+
+/// Representation of a region, country or administrative area in locations api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct LocationArea {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub localized_name: String,
+    pub english_name: String,
+}
+
+/// Representation of a time zone in locations api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct LocationTimeZone {
+    pub code: String,
+    pub name: String,
+    pub gmt_offset: f32,
+    pub is_daylight_saving: bool,
+    pub next_offset_change: Option<String>,
+}
+
+/// Representation of a geographic position in locations api.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct GeoPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: ConditionMeasurement,
+}
+
+/// Representation of a location in locations api.
+///
+/// `key` is the location to set on the client.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Location {
+    pub key: String,
+    pub r#type: String,
+    pub rank: i32,
+    pub localized_name: String,
+    pub english_name: String,
+    pub region: LocationArea,
+    pub country: LocationArea,
+    pub administrative_area: LocationArea,
+    pub time_zone: Option<LocationTimeZone>,
+    pub geo_position: GeoPosition,
+    /// fields of the answer not modeled by this crate
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
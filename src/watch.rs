@@ -0,0 +1,363 @@
+//! Watching locations for weather changes
+//!
+//! A `Watcher` keeps the last snapshot of the current conditions and daily forecasts of
+//! each location, and turns every new snapshot into `WatchEvent`s following its
+//! `WatchRules`. Snapshots are given with `update_conditions` and `update_daily_forecasts`,
+//! which do no i/o, so they fit any way of fetching the answers. `poll`, `run` and `spawn`
+//! fetch them with the blocking client, on a schedule for the last two, `spawn` on its own
+//! thread until its `WatchHandle` is stopped or dropped. `poll_async` fetches them through
+//! the async transport of the client, the caller awaiting it on its own schedule.
+use crate::config::Units;
+use crate::periods::DailyPeriod;
+use crate::schedule::{Answer, Endpoint};
+use crate::types::{CurrentCondition, DailyForecastsAnswer, HeadlineCategory, PrecipitationType};
+use crate::Accuweather;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A change between two snapshots of a location
+#[derive(Clone, Debug, PartialEq)]
+pub enum WatchEvent {
+    /// the current conditions report precipitation, and the previous ones didn't
+    PrecipitationStarted {
+        location: i32,
+        precipitation_type: Option<PrecipitationType>,
+    },
+    /// the current conditions report no precipitation, and the previous ones did
+    PrecipitationStopped { location: i32 },
+    /// the current temperature went across one of the thresholds, upwards when `rising`
+    TemperatureCrossed {
+        location: i32,
+        threshold: f32,
+        temperature: f32,
+        rising: bool,
+    },
+    /// the rain probability of a forecast day, the highest of its day and night, changed
+    /// by at least `WatchRules::rain_probability_change` points
+    RainProbabilityChanged {
+        location: i32,
+        date: String,
+        from: i32,
+        to: i32,
+    },
+    /// the daily forecasts have a headline different from the previous one
+    NewHeadline {
+        location: i32,
+        text: String,
+        category: HeadlineCategory,
+        severity: i32,
+    },
+    /// fetching the location failed while polling, its snapshots are kept
+    Failed { location: i32, message: String },
+}
+
+/// What a `Watcher` reports
+#[derive(Clone, Debug)]
+pub struct WatchRules {
+    /// temperatures whose crossing is reported, in `units`
+    pub temperature_thresholds: Vec<f32>,
+    /// unit system of the temperature thresholds
+    pub units: Units,
+    /// smallest change of rain probability reported, in percentage points
+    pub rain_probability_change: i32,
+    /// whether polling fetches the current conditions
+    pub conditions: bool,
+    /// period of the daily forecasts fetched by polling, if any
+    pub daily_forecasts: Option<DailyPeriod>,
+}
+
+impl Default for WatchRules {
+    fn default() -> Self {
+        WatchRules {
+            temperature_thresholds: Vec::new(),
+            units: Units::Metric,
+            rain_probability_change: 20,
+            conditions: true,
+            daily_forecasts: Some(DailyPeriod::Days5),
+        }
+    }
+}
+
+/// Last snapshots of watched locations, compared with the new ones
+#[derive(Debug, Default)]
+pub struct Watcher {
+    rules: WatchRules,
+    conditions: HashMap<i32, CurrentCondition>,
+    daily_forecasts: HashMap<i32, DailyForecastsAnswer>,
+}
+
+impl Watcher {
+    pub fn new(rules: WatchRules) -> Self {
+        Watcher {
+            rules,
+            conditions: HashMap::new(),
+            daily_forecasts: HashMap::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &WatchRules {
+        &self.rules
+    }
+
+    /// Last current conditions of a location
+    pub fn last_conditions(&self, location: i32) -> Option<&CurrentCondition> {
+        self.conditions.get(&location)
+    }
+
+    /// Last daily forecasts of a location
+    pub fn last_daily_forecasts(&self, location: i32) -> Option<&DailyForecastsAnswer> {
+        self.daily_forecasts.get(&location)
+    }
+
+    /// Compare current conditions with the last ones of the location, and keep them as
+    /// its snapshot
+    ///
+    /// Returns the events of the change, none for the first snapshot of a location
+    pub fn update_conditions(
+        &mut self,
+        location: i32,
+        conditions: CurrentCondition,
+    ) -> Vec<WatchEvent> {
+        let mut events = Vec::new();
+        if let Some(last) = self.conditions.get(&location) {
+            if conditions.has_precipitation && !last.has_precipitation {
+                events.push(WatchEvent::PrecipitationStarted {
+                    location,
+                    precipitation_type: conditions.precipitation_type.clone(),
+                });
+            } else if !conditions.has_precipitation && last.has_precipitation {
+                events.push(WatchEvent::PrecipitationStopped { location });
+            }
+            let temperature = |c: &CurrentCondition| match self.rules.units {
+                Units::Metric => c.temperature.metric.value,
+                Units::Imperial => c.temperature.imperial.value,
+            };
+            let (from, to) = (temperature(last), temperature(&conditions));
+            for &threshold in &self.rules.temperature_thresholds {
+                if from < threshold && to >= threshold {
+                    events.push(WatchEvent::TemperatureCrossed {
+                        location,
+                        threshold,
+                        temperature: to,
+                        rising: true,
+                    });
+                } else if from >= threshold && to < threshold {
+                    events.push(WatchEvent::TemperatureCrossed {
+                        location,
+                        threshold,
+                        temperature: to,
+                        rising: false,
+                    });
+                }
+            }
+        }
+        self.conditions.insert(location, conditions);
+        events
+    }
+
+    /// Compare daily forecasts with the last ones of the location, and keep them as its
+    /// snapshot
+    ///
+    /// Only days present in both forecasts are compared.
+    /// Returns the events of the change, none for the first snapshot of a location
+    pub fn update_daily_forecasts(
+        &mut self,
+        location: i32,
+        forecasts: DailyForecastsAnswer,
+    ) -> Vec<WatchEvent> {
+        let mut events = Vec::new();
+        if let Some(last) = self.daily_forecasts.get(&location) {
+            let headline = &forecasts.headline;
+            if headline.text != last.headline.text
+                || headline.effective_date != last.headline.effective_date
+            {
+                events.push(WatchEvent::NewHeadline {
+                    location,
+                    text: headline.text.clone(),
+                    category: headline.category.clone(),
+                    severity: headline.severity,
+                });
+            }
+            for day in &forecasts.daily_forecasts {
+                let previous = last
+                    .daily_forecasts
+                    .iter()
+                    .find(|previous| previous.date == day.date);
+                if let Some(previous) = previous {
+                    let from = previous
+                        .day
+                        .rain_probability
+                        .max(previous.night.rain_probability);
+                    let to = day.day.rain_probability.max(day.night.rain_probability);
+                    if from != to && (to - from).abs() >= self.rules.rain_probability_change {
+                        events.push(WatchEvent::RainProbabilityChanged {
+                            location,
+                            date: day.date.clone(),
+                            from,
+                            to,
+                        });
+                    }
+                }
+            }
+        }
+        self.daily_forecasts.insert(location, forecasts);
+        events
+    }
+
+    /// Fetch the endpoints chosen by the rules for each location, concurrently, and
+    /// update the snapshots
+    ///
+    /// Returns the events of the changes, with a `Failed` event for each failed fetch
+    pub fn poll(&mut self, client: &Accuweather, locations: &[i32]) -> Vec<WatchEvent> {
+        let mut events = Vec::new();
+        if self.rules.conditions {
+            let mut results: Vec<_> = client
+                .get_current_conditions_batch(locations)
+                .into_iter()
+                .collect();
+            results.sort_by_key(|(location, _)| *location);
+            for (location, result) in results {
+                match result.map(|conditions| conditions.into_iter().next()) {
+                    Ok(Some(conditions)) => {
+                        events.extend(self.update_conditions(location, conditions))
+                    }
+                    Ok(None) => {}
+                    Err(error) => events.push(failed(location, &*error)),
+                }
+            }
+        }
+        if let Some(period) = self.rules.daily_forecasts {
+            let mut results: Vec<_> = client
+                .get_daily_forecasts_batch(locations, period)
+                .into_iter()
+                .collect();
+            results.sort_by_key(|(location, _)| *location);
+            for (location, result) in results {
+                match result {
+                    Ok(forecasts) => {
+                        events.extend(self.update_daily_forecasts(location, forecasts))
+                    }
+                    Err(error) => events.push(failed(location, &*error)),
+                }
+            }
+        }
+        events
+    }
+
+    /// Fetch the endpoints chosen by the rules for each location through the async
+    /// transport of the client, one request at a time, and update the snapshots
+    ///
+    /// See `Accuweather::get_endpoint_async`. Waiting between two polls is left to the
+    /// runtime of the caller.
+    /// Returns the events of the changes, with a `Failed` event for each failed fetch
+    pub async fn poll_async(&mut self, client: &Accuweather, locations: &[i32]) -> Vec<WatchEvent> {
+        let mut locations = locations.to_vec();
+        locations.sort_unstable();
+        let mut endpoints = Vec::new();
+        if self.rules.conditions {
+            endpoints.push(Endpoint::CurrentConditions);
+        }
+        if let Some(period) = self.rules.daily_forecasts {
+            endpoints.push(Endpoint::DailyForecasts(period));
+        }
+        let mut events = Vec::new();
+        for endpoint in endpoints {
+            for &location in &locations {
+                match client.get_endpoint_async(location, endpoint).await {
+                    Ok(Answer::CurrentConditions(conditions)) => {
+                        if let Some(conditions) = conditions.into_iter().next() {
+                            events.extend(self.update_conditions(location, conditions));
+                        }
+                    }
+                    Ok(Answer::DailyForecasts(forecasts)) => {
+                        events.extend(self.update_daily_forecasts(location, forecasts))
+                    }
+                    Ok(_) => {}
+                    Err(error) => events.push(failed(location, &*error)),
+                }
+            }
+        }
+        events
+    }
+
+    /// Poll the locations every `interval`, giving each event to `callback`, until it
+    /// returns false
+    pub fn run<F: FnMut(WatchEvent) -> bool>(
+        &mut self,
+        client: &Accuweather,
+        locations: &[i32],
+        interval: Duration,
+        mut callback: F,
+    ) {
+        loop {
+            for event in self.poll(client, locations) {
+                if !callback(event) {
+                    return;
+                }
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    /// Poll the locations every `interval` on a new thread, sending the events to the
+    /// returned handle
+    ///
+    /// The thread stops when the handle is stopped or dropped, at once when it is waiting
+    /// for the next poll, else once the ongoing poll is done.
+    pub fn spawn(
+        mut self,
+        client: Accuweather,
+        locations: Vec<i32>,
+        interval: Duration,
+    ) -> WatchHandle {
+        let (sender, events) = mpsc::channel();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || loop {
+            for event in self.poll(&client, &locations) {
+                if sender.send(event).is_err() {
+                    return;
+                }
+            }
+            if stopped.recv_timeout(interval) != Err(RecvTimeoutError::Timeout) {
+                return;
+            }
+        });
+        WatchHandle {
+            events,
+            stop,
+            thread,
+        }
+    }
+}
+
+/// A `Watcher` polling on its own thread, see `Watcher::spawn`
+#[derive(Debug)]
+pub struct WatchHandle {
+    events: Receiver<WatchEvent>,
+    /// dropped to stop the thread
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl WatchHandle {
+    /// Events of the polls, in order
+    pub fn events(&self) -> &Receiver<WatchEvent> {
+        &self.events
+    }
+
+    /// Stop polling, waiting for the ongoing poll if any
+    pub fn stop(self) {
+        drop(self.stop);
+        let _ = self.thread.join();
+    }
+}
+
+/// Event of a location whose fetch failed
+fn failed(location: i32, error: &dyn std::error::Error) -> WatchEvent {
+    WatchEvent::Failed {
+        location,
+        message: error.to_string(),
+    }
+}